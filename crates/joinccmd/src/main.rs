@@ -1,10 +1,17 @@
+mod table;
+
 use clap::{Parser, Subcommand, ValueEnum};
 use libjoinc::defs::*;
 use libjoinc::error::*;
+use libjoinc::format::{format_timestamp, TimeStyle};
 use libjoinc::rpc::commands::*;
-use libjoinc::rpc::connection;
+use libjoinc::rpc::connection::{self, RpcTransport};
 use libjoinc::types::*;
+use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use table::{terminal_width, Align, Table};
 
 use chrono::prelude::*;
 
@@ -13,26 +20,77 @@ static INDENT2: &str = "  ";
 static INDENT3: &str = "   ";
 static INDENT4: &str = "    ";
 
+// exit codes on failure, see the help text of Cli
+static EXIT_CONNECTION: i32 = 1;
+static EXIT_AUTH: i32 = 2;
+static EXIT_REJECTED: i32 = 3;
+static EXIT_DESERIALIZATION: i32 = 4;
+static EXIT_INVALID_ARGUMENT: i32 = 5;
+
 #[derive(Parser)]
+#[command(after_help = "Exit codes on failure (except for check):
+  1  connecting or communicating with the client failed
+  2  authentication failed
+  3  the client rejected the operation
+  4  the client's reply couldn't be read
+  5  an argument was invalid")]
 struct Cli {
-    /// Name of the host where the BOINC client is running
-    #[arg(long, default_value = "localhost")]
-    host: String,
+    /// Name of the host where the BOINC client is running [default: localhost]
+    #[arg(long)]
+    host: Option<String>,
 
-    /// Port on which the BOINC client is listening
-    #[arg(long,default_value_t=connection::DEFAULT_PORT)]
-    port: u16,
+    /// Port on which the BOINC client is listening [default: 31416]
+    #[arg(long)]
+    port: Option<u16>,
 
-    /// Password to authenticate against the BOINC client
+    /// Password to authenticate against the BOINC client, an empty one skips the authentication
     #[arg(long)]
     passwd: Option<String>,
 
+    /// Skip the authentication, e.g. for clients without a password, despite one in the config file
+    #[arg(long, conflicts_with = "passwd")]
+    no_auth: bool,
+
+    /// Use the given host section of the config file
+    #[arg(long)]
+    profile: Option<String>,
+
+    /// Connect through the SOCKS5 proxy at the given address, e.g. bastion.lan:1080
+    #[arg(long, value_name = "ADDR")]
+    socks5: Option<String>,
+
+    /// Run the command against all hosts of the config file
+    #[arg(long, conflicts_with_all = ["host", "port", "profile"])]
+    all_hosts: bool,
+
+    /// Allow commands changing the clients' state together with --all-hosts
+    #[arg(long, requires = "all_hosts")]
+    confirm_all: bool,
+
+    /// Stop operations on several targets at the first failure,
+    /// e.g. of suspend-all-tasks, retry-all-transfers or --all-hosts
+    #[arg(long, overrides_with = "keep_going")]
+    fail_fast: bool,
+
+    /// Continue operations on several targets after a failure, the default
+    #[arg(long, overrides_with = "fail_fast")]
+    keep_going: bool,
+
+    /// How to display timestamps, e.g. iso8601 for log ingestion
+    #[arg(long, value_enum, default_value = "local")]
+    time_format: TimeFormat,
+
     #[command(subcommand)]
     command: CliCommand,
 }
 
-#[derive(Subcommand, PartialEq)]
+#[derive(Clone, Subcommand, PartialEq)]
 enum CliCommand {
+    /// Check if the client is computing, e.g. for monitoring
+    ///
+    /// Exits with 0 if the client is computing, with 2 if it's suspended,
+    /// with 1 if connecting or authenticating failed and with 5 if an argument was invalid.
+    Check,
     /// Show client version
     ClientVersion,
     /// Execute an operation on a file transfer
@@ -45,36 +103,154 @@ enum CliCommand {
         /// The operation to execute
         #[arg(value_enum)]
         op: SupportedFileTransferOp,
+        /// The output format, json reports the outcome
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
     },
     /// Show cc status
     GetCCStatus,
+    /// Show the rate of credit granted to each project over a sampling interval
+    GetCreditRate {
+        /// Seconds between the two samples
+        #[arg(long, default_value = "60", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+    },
     /// Show disk usage
-    GetDiskUsage,
+    GetDiskUsage {
+        /// Show each project's share of the total disk space as percentage and bar
+        #[arg(long)]
+        bars: bool,
+        /// Sort the projects by disk usage, largest first, or by master URL
+        #[arg(long, value_enum)]
+        sort: Option<DiskUsageSort>,
+        /// Hide the projects using less than the given MiB, the totals are unaffected
+        #[arg(long, value_name = "N")]
+        min_mb: Option<u64>,
+        /// Show the projects as a table
+        #[arg(long, conflicts_with = "bars")]
+        table: bool,
+    },
     /// Show host info
-    GetHostInfo,
+    GetHostInfo {
+        /// Show the exact sizes in bytes
+        #[arg(long)]
+        verbose: bool,
+        /// The output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
     /// Show file transfers
-    GetFileTransfers,
+    GetFileTransfers {
+        /// Show stalled transfers only, i.e. retried, backed off or active without progress
+        #[arg(long)]
+        stalled: bool,
+        /// Retry the stalled transfers
+        #[arg(long)]
+        retry_stalled: bool,
+    },
+    /// Show the event log flags of cc_config.xml and which are enabled
+    GetLogFlags,
     /// Show messages
     GetMessages {
         /// Show messages with sequence number > seqno only
         #[arg(default_value = "0")]
         seqno: u32,
+        /// Show messages with the given priorities only, defaults to all
+        #[arg(long, value_enum, value_delimiter = ',')]
+        priority: Vec<SupportedMsgPriority>,
+        /// Show messages whose body matches the regular expression only
+        #[arg(long)]
+        grep: Option<String>,
+        /// Match the --grep pattern case-sensitively
+        #[arg(long, requires = "grep")]
+        case_sensitive: bool,
     },
     /// Show notices
     GetNotices {
         /// Show notices with sequence number > seqno only
         #[arg(default_value = "0")]
         seqno: u32,
+        /// Show notices of the given categories only, defaults to all
+        #[arg(long, value_enum, value_delimiter = ',')]
+        category: Vec<SupportedNoticeCategory>,
+        /// Show private notices as well
+        #[arg(long)]
+        include_private: bool,
+        /// The output format, rss emits an RSS 2.0 feed for feed readers
+        #[arg(long, value_enum, default_value = "text")]
+        format: NoticeFormat,
     },
+    /// Show the platforms the client can run applications for
+    GetPlatforms,
     /// Show status of all attached projects
     #[command(visible_alias = "get-project-status")]
-    GetProjects,
+    GetProjects {
+        /// Show only projects not requesting more work
+        #[arg(long)]
+        no_more_work: bool,
+        /// Show only projects suspended via GUI
+        #[arg(long)]
+        suspended: bool,
+        /// Show only projects with failed scheduler RPCs or master fetches
+        #[arg(long)]
+        errors: bool,
+        /// Show host and scheduling details
+        #[arg(long)]
+        verbose: bool,
+        /// Sort by scheduling priority, the project fetching work next first
+        #[arg(long)]
+        by_priority: bool,
+        /// The output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Show the client's state
+    GetState {
+        /// Show only the given comma separated sections,
+        /// i.e. projects, tasks, host, apps, time-stats, net-stats
+        #[arg(long, value_parser = parse_state_sections)]
+        only: Option<StateSections>,
+    },
     /// Show tasks
     #[command(visible_alias = "get-results")]
     GetTasks {
         /// Show only active tasks
         #[arg(long)]
         active_only: bool,
+        /// Show only tasks using a GPU
+        #[arg(long, conflicts_with = "cpu_only")]
+        gpu_only: bool,
+        /// Show only tasks not using a GPU
+        #[arg(long)]
+        cpu_only: bool,
+        /// Show the exact sizes in bytes
+        #[arg(long)]
+        verbose: bool,
+        /// Print one JSON object per task and line
+        #[arg(long)]
+        json_lines: bool,
+        /// Show the active tasks, the ones waiting to be reported and the errored ones separately
+        #[arg(long, conflicts_with = "json_lines")]
+        grouped: bool,
+        /// Show only unreported tasks due within the given hours, nearest deadline first
+        #[arg(long, value_name = "HOURS", conflicts_with_all = ["json_lines", "grouped"],
+            value_parser = clap::value_parser!(u64).range(1..))]
+        at_risk: Option<u64>,
+        /// Show the bytes sent and received by all active tasks in total
+        #[arg(long, conflicts_with = "json_lines")]
+        network_summary: bool,
+        /// Show the tasks as a table
+        #[arg(long, conflicts_with_all = ["json_lines", "grouped", "at_risk", "verbose"])]
+        table: bool,
+    },
+    /// Show the projects known to BOINC, which may be attached to
+    ListProjects {
+        /// Show only projects advertising app versions for the client's platforms
+        #[arg(long)]
+        compatible: bool,
+        /// Show the projects as a table
+        #[arg(long)]
+        table: bool,
     },
     /// Retry deferred network communication
     NetworkAvailable,
@@ -86,6 +262,9 @@ enum CliCommand {
         /// The operation to execute
         #[arg(value_enum)]
         op: SupportedProjectOp,
+        /// The output format, json reports the outcome
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
     },
     /// Tell the client to quit
     Quit,
@@ -93,35 +272,81 @@ enum CliCommand {
     ReadCcConfig,
     /// Read the global_prefs_override.xml file
     ReadGlobalPrefsOverride,
+    /// Resume all active tasks suspended via GUI
+    ResumeAllTasks,
+    /// Retry all file transfers
+    RetryAllTransfers {
+        /// Retry stalled transfers only
+        #[arg(long)]
+        stalled_only: bool,
+        /// Don't ask for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
     /// Run the benchmarks
     RunBenchmarks,
+    /// Compute until no unfinished tasks are left, then restore the run mode
+    ///
+    /// Sets the run mode to always, e.g. for crunching the tasks at hand before shutting down.
+    /// Set the projects to no more work first, otherwise the client keeps fetching new tasks.
+    RunUntilIdle {
+        /// Seconds between two checks for unfinished tasks
+        #[arg(long, default_value = "60", value_parser = clap::value_parser!(u64).range(1..))]
+        interval: u64,
+        /// Seconds after which the run mode is restored even if tasks are left
+        #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+        max_duration: Option<u64>,
+    },
     /// Set gpu run mode for given duration
     SetGpuMode {
         /// The mode to run
         #[arg(value_enum)]
         mode: SupportedRunMode,
-        /// The duration this mode to be set
-        #[arg(default_value = "0")]
+        /// The duration this mode to be set in seconds, 0 to set it permanently
+        #[arg(default_value = "0", value_parser = parse_mode_duration)]
         duration: f64,
+        /// The output format, json reports the outcome
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Enable or disable an event log flag of cc_config.xml and apply it
+    ///
+    /// Changes only the flag, the other log flags and options of cc_config.xml are kept as they are.
+    SetLogFlag {
+        /// The flag's name, e.g. cpu_sched, see get-log-flags
+        flag: String,
+        /// Whether to enable the flag, e.g. on or off
+        #[arg(action = clap::ArgAction::Set, value_parser = clap::builder::BoolishValueParser::new())]
+        enabled: bool,
     },
     /// Set network mode for given duration
     SetNetworkMode {
         /// The mode to run
         #[arg(value_enum)]
         mode: SupportedRunMode,
-        /// The duration this mode to be set
-        #[arg(default_value = "0")]
+        /// The duration this mode to be set in seconds, 0 to set it permanently
+        #[arg(default_value = "0", value_parser = parse_mode_duration)]
         duration: f64,
+        /// The output format, json reports the outcome
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
     },
     /// Set run mode for given duration
     SetRunMode {
         /// The mode to run
         #[arg(value_enum)]
         mode: SupportedRunMode,
-        /// The duration this mode to be set
-        #[arg(default_value = "0")]
+        /// The duration this mode to be set in seconds, 0 to set it permanently
+        #[arg(default_value = "0", value_parser = parse_mode_duration)]
         duration: f64,
+        /// The output format, json reports the outcome
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
     },
+    /// Show a compact overview of run modes and tasks
+    Status,
+    /// Suspend all active tasks, leaving the run mode untouched
+    SuspendAllTasks,
     /// Execute an operation a task
     #[command(visible_alias = "task")]
     #[command(visible_alias = "result-op")]
@@ -134,106 +359,1087 @@ enum CliCommand {
         /// The operation to execute
         #[arg(value_enum)]
         op: SupportedTaskOp,
+        /// The output format, json reports the outcome
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
+    /// Save the client's state to a file, e.g. for state-diff
+    SaveState {
+        /// The file to write the state to, for --all-hosts suffixed with each host's name,
+        /// e.g. state.cruncher.xml for state.xml
+        path: std::path::PathBuf,
+    },
+    /// Show what changed between two saved client states, needs no connection
+    StateDiff {
+        /// The earlier state, e.g. a saved get_state reply or client_state.xml
+        file_a: std::path::PathBuf,
+        /// The later state
+        file_b: std::path::PathBuf,
     },
     /// Show the version of this cli
-    Version,
+    Version {
+        /// The output format
+        #[arg(long, value_enum, default_value = "text")]
+        format: OutputFormat,
+    },
 }
 
 fn main() {
-    let cli = Cli::parse();
+    let cli = Cli::try_parse().unwrap_or_else(|err| {
+        let _ = err.print();
+        std::process::exit(parse_error_exit_code(&err));
+    });
+    let time_style = TimeStyle::from(cli.time_format);
+    let on_error = on_error(&cli);
+
+    if let CliCommand::Version { format } = cli.command {
+        match format {
+            OutputFormat::Text => println!("Version: {JOINCCMD_VERSION}"),
+            OutputFormat::Json => println!("{}", version_json()),
+        }
+        std::process::exit(0);
+    }
 
-    if cli.command == CliCommand::Version {
-        println!("Version: {JOINCCMD_VERSION}");
+    if let CliCommand::StateDiff { file_a, file_b } = &cli.command {
+        let (a, b) = load_state(file_a).and_then(|a| Ok((a, load_state(file_b)?))).unwrap_or_else(|err| {
+            eprintln!("Failed to load state: {}", err);
+            std::process::exit(1);
+        });
+        for change in diff_states(&a, &b) {
+            println!("{}", change.display());
+        }
         std::process::exit(0);
     }
 
-    let mut connection = connection::Connection::open(&cli.host, cli.port).unwrap_or_else(|err| {
+    // check documents to fail with 1 only, as 2 means suspended there
+    let is_check = cli.command == CliCommand::Check;
+    let exit_code_unless_check = |code: i32| if is_check { 1 } else { code };
+
+    let config = load_config().unwrap_or_else(|err| {
+        eprintln!("Failed to read config file: {}", err);
+        std::process::exit(exit_code_unless_check(EXIT_CONNECTION));
+    });
+    if cli.all_hosts {
+        if is_check {
+            eprintln!("The check command doesn't support --all-hosts");
+            std::process::exit(EXIT_INVALID_ARGUMENT);
+        }
+        if !is_read_only(&cli.command) && !cli.confirm_all {
+            eprintln!("The command changes the clients' state, pass --confirm-all to run it against all hosts");
+            std::process::exit(EXIT_INVALID_ARGUMENT);
+        }
+        let mut names: Vec<&String> = config.host.keys().collect();
+        names.sort();
+        let targets: Vec<(String, Target)> = names
+            .into_iter()
+            .map(|name| {
+                let cli_profile = Profile { host: None, port: None, passwd: cli_passwd(&cli) };
+                let target = resolve_target(cli_profile, &config, Some(name)).expect("profile exists");
+                (name.clone(), target)
+            })
+            .collect();
+        let failed = run_on_all_hosts(&targets, |target| connect(target, cli.socks5.as_deref()), &cli.command, on_error, time_style);
+        std::process::exit(if failed > 0 { EXIT_CONNECTION } else { 0 });
+    }
+
+    let cli_profile = Profile { passwd: cli_passwd(&cli), host: cli.host, port: cli.port };
+    let target = resolve_target(cli_profile, &config, cli.profile.as_deref()).unwrap_or_else(|err| {
+        eprintln!("{}", err);
+        std::process::exit(EXIT_INVALID_ARGUMENT);
+    });
+
+    let mut connection = open_connection(&target, cli.socks5.as_deref()).unwrap_or_else(|err| {
         eprintln!("Failed to connect to BOINC client: {}", err.display());
-        std::process::exit(1);
+        std::process::exit(exit_code_unless_check(EXIT_CONNECTION));
     });
 
-    if let Some(passwd) = cli.passwd.as_deref() {
+    if let Some(passwd) = target.passwd.as_deref() {
         AuthorizeCommand::new(passwd)
             .execute(&mut connection)
-            .unwrap_or_else(|_| {
+            .unwrap_or_else(|err| {
                 eprintln!("Authentication failed. Incorrect password?");
-                std::process::exit(1);
+                std::process::exit(exit_code_unless_check(match err {
+                    Error::Io(_) | Error::Rpc(_) => EXIT_CONNECTION,
+                    _ => EXIT_AUTH,
+                }));
             });
     }
 
-    process_command(&mut connection, cli.command).unwrap_or_else(|err| {
+    process_command(&mut connection, cli.command, on_error, time_style).unwrap_or_else(|err| {
+        let code = exit_code(&err);
         eprintln!("The command failed with: {}", err.display());
-        std::process::exit(1);
+        std::process::exit(exit_code_unless_check(code));
+    });
+}
+
+#[cfg(feature = "socks")]
+fn open_connection(target: &Target, socks5: Option<&str>) -> Result<connection::Connection> {
+    match socks5 {
+        Some(proxy) => connection::Connection::open_via_socks5(proxy, &target.host, target.port),
+        None => connection::Connection::open(&target.host, target.port),
+    }
+}
+
+#[cfg(not(feature = "socks"))]
+fn open_connection(target: &Target, socks5: Option<&str>) -> Result<connection::Connection> {
+    match socks5 {
+        Some(_) => Err(Error::Client("joinccmd was built without feature socks".to_string())),
+        None => connection::Connection::open(&target.host, target.port),
+    }
+}
+
+fn connect(target: &Target, socks5: Option<&str>) -> Result<connection::Connection> {
+    let mut connection = open_connection(target, socks5)?;
+    if let Some(passwd) = target.passwd.as_deref() {
+        AuthorizeCommand::new(passwd).execute(&mut connection)?;
+    }
+    Ok(connection)
+}
+
+// Runs the command against each host connected to by connect_to in turn,
+// a failing host aborts the others for OnError::Stop only.
+// Returns the number of failed hosts.
+fn run_on_all_hosts<T: RpcTransport>(
+    targets: &[(String, Target)],
+    mut connect_to: impl FnMut(&Target) -> Result<T>,
+    command: &CliCommand,
+    on_error: OnError,
+    time_style: TimeStyle,
+) -> usize {
+    let results = run_each(targets, on_error, |(name, target)| {
+        println!("######## {} ########", name);
+        let command = match command {
+            CliCommand::SaveState { path } => CliCommand::SaveState { path: path_for_host(path, name) },
+            command => command.clone(),
+        };
+        let result = connect_to(target)
+            .and_then(|mut connection| process_command(&mut connection, command, on_error, time_style));
+        if let Err(err) = &result {
+            eprintln!("The command failed on {} with: {}", name, err.display());
+        }
+        result
     });
+    results.iter().filter(|result| result.is_err()).count()
 }
 
-fn process_command(connection: &mut connection::Connection, command: CliCommand) -> Result<()> {
+// The path with the host's name inserted before the extension, so the hosts don't overwrite each other's file.
+fn path_for_host(path: &std::path::Path, host: &str) -> std::path::PathBuf {
+    let mut file_name = path.file_stem().unwrap_or_default().to_os_string();
+    file_name.push(".");
+    file_name.push(host);
+    if let Some(extension) = path.extension() {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+    path.with_file_name(file_name)
+}
+
+// what operations on several targets do on a failed target
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum OnError {
+    Stop,
+    Continue,
+}
+
+// the later of --fail-fast and --keep-going overrides the other
+fn on_error(cli: &Cli) -> OnError {
+    match (cli.fail_fast, cli.keep_going) {
+        (true, false) => OnError::Stop,
+        _ => OnError::Continue,
+    }
+}
+
+// Runs the operation on each target in turn and collects the results,
+// skipping the targets after a failure for OnError::Stop.
+fn run_each<T>(targets: &[T], on_error: OnError, mut op: impl FnMut(&T) -> Result<()>) -> Vec<Result<()>> {
+    let mut results = vec![];
+    for target in targets {
+        let result = op(target);
+        let failed = result.is_err();
+        results.push(result);
+        if failed && on_error == OnError::Stop {
+            break;
+        }
+    }
+    results
+}
+
+// the first failure of the results, failing the command even if it continued after it
+fn first_failure(results: Vec<Result<()>>) -> Result<()> {
+    results.into_iter().find(Result::is_err).unwrap_or(Ok(()))
+}
+
+fn is_read_only(command: &CliCommand) -> bool {
     match command {
+        CliCommand::GetFileTransfers { retry_stalled, .. } => !retry_stalled,
+        CliCommand::Check
+        | CliCommand::ClientVersion
+        | CliCommand::GetCCStatus
+        | CliCommand::GetCreditRate { .. }
+        | CliCommand::GetDiskUsage { .. }
+        | CliCommand::GetHostInfo { .. }
+        | CliCommand::GetLogFlags
+        | CliCommand::GetMessages { .. }
+        | CliCommand::GetNotices { .. }
+        | CliCommand::GetPlatforms
+        | CliCommand::GetProjects { .. }
+        | CliCommand::GetState { .. }
+        | CliCommand::GetTasks { .. }
+        | CliCommand::ListProjects { .. }
+        | CliCommand::SaveState { .. }
+        | CliCommand::Status
+        | CliCommand::StateDiff { .. }
+        | CliCommand::Version { .. } => true,
+        CliCommand::FileTransferOp { .. }
+        | CliCommand::NetworkAvailable
+        | CliCommand::ProjectOp { .. }
+        | CliCommand::Quit
+        | CliCommand::ReadCcConfig
+        | CliCommand::ReadGlobalPrefsOverride
+        | CliCommand::ResumeAllTasks
+        | CliCommand::RetryAllTransfers { .. }
+        | CliCommand::RunBenchmarks
+        | CliCommand::RunUntilIdle { .. }
+        | CliCommand::SetGpuMode { .. }
+        | CliCommand::SetLogFlag { .. }
+        | CliCommand::SetNetworkMode { .. }
+        | CliCommand::SetRunMode { .. }
+        | CliCommand::SuspendAllTasks
+        | CliCommand::TaskOp { .. } => false,
+    }
+}
+
+fn exit_code(err: &Error) -> i32 {
+    match err {
+        Error::Io(_) | Error::Rpc(_) => EXIT_CONNECTION,
+        Error::Unauthorized => EXIT_AUTH,
+        Error::Client(_) | Error::Rejected(..) => EXIT_REJECTED,
+        Error::Deserialization(_) => EXIT_DESERIALIZATION,
+        Error::InvalidArgument(_) => EXIT_INVALID_ARGUMENT,
+    }
+}
+
+// clap's own exit code for invalid arguments is 2, which is taken by failed authentications
+fn parse_error_exit_code(err: &clap::Error) -> i32 {
+    match err.kind() {
+        clap::error::ErrorKind::DisplayHelp | clap::error::ErrorKind::DisplayVersion => 0,
+        _ => EXIT_INVALID_ARGUMENT,
+    }
+}
+
+fn process_command(connection: &mut dyn RpcTransport, command: CliCommand, on_error: OnError, time_style: TimeStyle) -> Result<()> {
+    match command {
+        CliCommand::Check => {
+            let cc_status = GetCCStatusCommand::default().execute(connection)?;
+            match cc_status.task_suspend_reason {
+                SuspendReason::NotSuspended => println!("computing"),
+                reason => println!("suspended: {}", reason),
+            }
+            std::process::exit(check_exit_code(&cc_status));
+        }
         CliCommand::ClientVersion => {
             println!("Client version: {}", ExchangeVersionsCommand::default().execute(connection)?.display());
         }
-        CliCommand::FileTransferOp { project_url, filename, op } => {
-            FileTransferOpCommand::new(project_url, filename, op.into()).execute(connection)?;
+        CliCommand::FileTransferOp { project_url, filename, op, format } => {
+            let result = FileTransferOpCommand::new(project_url, filename.clone(), op.into()).execute(connection);
+            report_outcome(&format, "file-transfer-op", filename, result)?;
         }
         CliCommand::GetCCStatus => {
             print!("{}", GetCCStatusCommand::default().execute(connection)?.display());
         }
-        CliCommand::GetDiskUsage => {
+        CliCommand::GetDiskUsage { bars, sort, min_mb, table } => {
             println!("======== Disk usage ========");
-            print!("{}", GetDiskUsageSummaryCommand::default().execute(connection)?.display());
+            let mut disk_usage = GetDiskUsageSummaryCommand::default().execute(connection)?;
+            arrange_disk_usage(&mut disk_usage.projects, sort, min_mb.map(|mb| mb as f64 * 1024. * 1024.));
+            if table {
+                print!("{}", disk_usage_table(&disk_usage).max_width(terminal_width()));
+            } else if bars {
+                print!("{:#}", disk_usage.display());
+            } else {
+                print!("{}", disk_usage.display());
+            }
         }
-        CliCommand::GetHostInfo => {
-            print!("{}", GetHostInfoCommand::default().execute(connection)?.display());
+        CliCommand::GetHostInfo { verbose, format } => {
+            let host_info = GetHostInfoCommand::default().execute(connection)?;
+            match format {
+                OutputFormat::Json => println!("{}", host_info_json(&host_info)),
+                OutputFormat::Text if verbose => print!("{:#}", host_info.display()),
+                OutputFormat::Text => print!("{}", host_info.display()),
+            }
         }
-        CliCommand::GetFileTransfers => {
+        CliCommand::GetFileTransfers { stalled, retry_stalled } => {
+            let file_transfers = GetFileTransfersCommand::default().execute(connection)?;
+            let to_retry = if retry_stalled { select_file_transfers(&file_transfers, true) } else { vec![] };
+
             println!("======== File transfers ========");
-            for (idx, file_transfer) in GetFileTransfersCommand::default().execute(connection)?.into_iter().enumerate() {
+            for (idx, file_transfer) in file_transfers
+                .into_iter()
+                .filter(|file_transfer| !stalled || file_transfer.is_stalled())
+                .enumerate()
+            {
                 println!("{}) -----------", idx + 1);
                 print!("{}", file_transfer.display());
             }
+
+            if retry_stalled {
+                let results = retry_file_transfers(connection, &to_retry, on_error);
+                let retried = results.iter().filter(|result| result.is_ok()).count();
+                println!("Retried {} of {} stalled file transfers", retried, to_retry.len());
+                first_failure(results)?;
+            }
+        }
+        CliCommand::GetCreditRate { interval } => {
+            let before = GetProjectStatusCommand::default().execute(connection)?;
+            std::thread::sleep(std::time::Duration::from_secs(interval));
+            let after = GetProjectStatusCommand::default().execute(connection)?;
+
+            println!("======== Credit rates ========");
+            for rate in credit_rates(&before, &after, interval as f64) {
+                print!("{}", rate.display());
+            }
         }
-        CliCommand::GetMessages { seqno } => {
+        CliCommand::GetLogFlags => {
+            let client_version = ExchangeVersionsCommand::default().execute(connection)?;
+            println!("======== Log flags ========");
+            for (name, enabled) in GetCcConfigCommand::default().execute_for(connection, &client_version)?.log_flags.flags() {
+                println!("{}: {}", name, enabled.display());
+            }
+        }
+        CliCommand::GetMessages { seqno, priority, grep, case_sensitive } => {
+            let priorities: Vec<MsgInfo> = priority.into_iter().map(MsgInfo::from).collect();
+            let body_filter = grep
+                .map(|pattern| BodyFilter::new(&pattern, case_sensitive))
+                .transpose()
+                .unwrap_or_else(|err| {
+                    eprintln!("Invalid --grep pattern: {}", err);
+                    std::process::exit(EXIT_INVALID_ARGUMENT);
+                });
             for msg in GetMessagesCommand::new(seqno).execute(connection)? {
-                println!("{}", msg.display());
+                if has_priority(&msg, &priorities) && matches_body(&msg, body_filter.as_ref()) {
+                    println!("{}", msg.display_in(time_style));
+                }
             }
         }
-        CliCommand::GetNotices { seqno } => {
-            for notice in GetNoticesCommand::new(seqno).execute(connection)?.into_iter().rev() {
-                println!("{}", notice.display());
+        CliCommand::GetNotices { seqno, category, include_private, format } => {
+            let client_version = ExchangeVersionsCommand::default().execute(connection)?;
+            let notices: Vec<Notice> = GetNoticesCommand::new(seqno)
+                .execute_for(connection, &client_version)?
+                .into_iter()
+                .rev()
+                .filter(|notice| has_category(notice, &category) && (include_private || !bool::from(notice.is_private)))
+                .collect();
+            match format {
+                NoticeFormat::Rss => print!("{}", notices_feed(&notices)?),
+                NoticeFormat::Text => {
+                    for notice in notices {
+                        println!("{}", notice.display_in(time_style));
+                    }
+                }
             }
         }
-        CliCommand::GetProjects => {
-            println!("======== Projects ========");
-            for (idx, project) in GetProjectStatusCommand::default().execute(connection)?.into_iter().enumerate() {
-                println!("{}) -----------", idx + 1);
-                print!("{}", project.display());
+        CliCommand::GetPlatforms => {
+            println!("======== Platforms ========");
+            for (idx, platform) in GetStateCommand::default().execute(connection)?.platforms.iter().enumerate() {
+                println!("{}{}", platform, if idx == 0 { " (primary)" } else { "" });
             }
         }
-        CliCommand::GetTasks { active_only } => {
-            println!("======== Tasks ========");
-            for (idx, task) in GetResultsCommand::new(active_only).execute(connection)?.into_iter().enumerate() {
-                println!("{}) -----------", idx + 1);
-                print!("{}", task.display());
+        CliCommand::GetProjects { no_more_work, suspended, errors, verbose, by_priority, format } => {
+            let filter = ProjectFilter { no_more_work, suspended, errors };
+            let projects = GetProjectStatusCommand::default().execute(connection)?;
+            let shares = resource_share_percentages(&projects);
+            let mut projects: Vec<(Project, Option<f64>)> =
+                projects.into_iter().zip(shares).filter(|(project, _)| filter.matches(project)).collect();
+            let next = if by_priority { rank_by_priority(&mut projects) } else { None };
+            let (projects, shares): (Vec<Project>, Vec<Option<f64>>) = projects.into_iter().unzip();
+            let summary = summarize(&projects);
+            match format {
+                OutputFormat::Json if by_priority => {
+                    let next = next.map(|idx| &projects[idx].master_url);
+                    let json = serde_json::json!({ "projects": projects, "summary": summary, "next_to_fetch_work": next });
+                    println!("{json}");
+                }
+                OutputFormat::Json => {
+                    println!("{}", serde_json::json!({ "projects": projects, "summary": summary }));
+                }
+                OutputFormat::Text => {
+                    let count = projects.len();
+                    println!("======== Projects ========");
+                    for (idx, project) in projects.into_iter().zip(shares).enumerate() {
+                        println!("{}) -----------", idx + 1);
+                        if next == Some(idx) {
+                            println!("{INDENT3}next to fetch work");
+                        }
+                        if by_priority && !verbose {
+                            println!("{INDENT3}scheduling priority: {:.6}", project.0.sched_priority);
+                        }
+                        if verbose {
+                            print!("{:#}", project.display_in(time_style));
+                        } else {
+                            print!("{}", project.display_in(time_style));
+                        }
+                    }
+                    if count > 1 {
+                        println!("-----------");
+                        print!("{}", summary.display());
+                    }
+                }
+            }
+        }
+        CliCommand::GetState { only } => {
+            let sections = only.unwrap_or(StateSections::ALL);
+            let state = GetStateCommand::default().execute(connection)?;
+            if sections.contains(StateSections::HOST) {
+                println!("======== Host info ========");
+                print!("{}", state.host_info.display());
+            }
+            if sections.contains(StateSections::PROJECTS) {
+                let shares = resource_share_percentages(&state.projects);
+                println!("======== Projects ========");
+                for (idx, project) in state.projects.into_iter().zip(shares).enumerate() {
+                    println!("{}) -----------", idx + 1);
+                    print!("{}", project.display_in(time_style));
+                }
+            }
+            if sections.contains(StateSections::APPS) {
+                println!("======== Applications ========");
+                for (idx, app) in state.apps.into_iter().enumerate() {
+                    println!("{}) -----------", idx + 1);
+                    print!("{}", app.display());
+                }
+            }
+            if sections.contains(StateSections::TASKS) {
+                println!("======== Tasks ========");
+                for (idx, task) in state.tasks.into_iter().enumerate() {
+                    println!("{}) -----------", idx + 1);
+                    print!("{}", task.display_in(time_style));
+                }
+            }
+            if sections.contains(StateSections::TIME_STATS) {
+                println!("======== Time stats ========");
+                print!("{}", state.time_stats.display_in(time_style));
+            }
+            if sections.contains(StateSections::NET_STATS) {
+                println!("======== Network stats ========");
+                print!("{}", state.net_stats.display());
+            }
+        }
+        CliCommand::GetTasks {
+            active_only, gpu_only, cpu_only, verbose, json_lines, grouped, at_risk, network_summary, table,
+        } => {
+            let tasks: Vec<Task> = GetResultsCommand::new(active_only)
+                .execute(connection)?
+                .into_iter()
+                .filter(|task| (!gpu_only || uses_gpu(&task.resources)) && (!cpu_only || !uses_gpu(&task.resources)))
+                .collect();
+            let (sent, received) = network_totals(&tasks);
+            if json_lines {
+                write_json_lines(&mut io::stdout(), &tasks)?;
+            } else if table {
+                println!("======== Tasks ========");
+                print!("{}", tasks_table(&tasks, time_style).max_width(terminal_width()));
+            } else if grouped {
+                let groups = TaskGroups::from(tasks);
+                print_tasks("Active tasks", groups.active, verbose, time_style);
+                print_tasks("Tasks waiting to report", groups.waiting_to_report, verbose, time_style);
+                print_tasks("Errored tasks", groups.errored, verbose, time_style);
+            } else if let Some(hours) = at_risk {
+                let now = Timestamp(Utc::now().timestamp() as f64);
+                println!("======== Tasks at risk ========");
+                for (idx, task) in tasks_at_risk(tasks, now, hours as f64 * 3600.).into_iter().enumerate() {
+                    println!("{}) -----------", idx + 1);
+                    match task.report_deadline.0 - now.0 {
+                        remaining if remaining > 0. => println!("{INDENT3}time remaining: {}", FormattedDuration(remaining)),
+                        overdue => println!("{INDENT3}time remaining: none, overdue by {}", FormattedDuration(-overdue)),
+                    }
+                    if verbose {
+                        print!("{:#}", task.display_in(time_style));
+                    } else {
+                        print!("{}", task.display_in(time_style));
+                    }
+                }
+            } else {
+                print_tasks("Tasks", tasks, verbose, time_style);
+            }
+            if network_summary {
+                println!("======== Network summary ========");
+                if verbose {
+                    println!("{INDENT3}bytes sent: {:#} received: {:#}", Bytes(sent), Bytes(received));
+                } else {
+                    println!("{INDENT3}bytes sent: {} received: {}", Bytes(sent), Bytes(received));
+                }
+            }
+        }
+        CliCommand::ListProjects { compatible, table } => {
+            let mut projects = GetAllProjectsListCommand::default().execute(connection)?;
+            if compatible {
+                let platforms = GetStateCommand::default().execute(connection)?.platforms;
+                projects.retain(|project| project.is_compatible(&platforms));
+            }
+            println!("======== Projects ========");
+            if table {
+                print!("{}", project_list_table(&projects).max_width(terminal_width()));
+            } else {
+                for (idx, project) in projects.into_iter().enumerate() {
+                    println!("{}) -----------", idx + 1);
+                    print!("{}", project.display());
+                }
             }
         }
         CliCommand::NetworkAvailable => NetworkAvailableCommand::default().execute(connection)?,
-        CliCommand::ProjectOp { project_url, op } => ProjectOpCommand::new(project_url, op.into()).execute(connection)?,
+        CliCommand::ProjectOp { project_url, op, format } => {
+            let result = ProjectOpCommand::new(project_url.clone(), op.into()).execute(connection);
+            report_outcome(&format, "project-op", project_url, result)?;
+        }
         CliCommand::Quit => QuitCommand::default().execute(connection)?,
         CliCommand::ReadCcConfig => ReadCCConfigCommand::default().execute(connection)?,
         CliCommand::ReadGlobalPrefsOverride => ReadGlobalPreferencesOverrideCommand::default().execute(connection)?,
+        CliCommand::ResumeAllTasks => {
+            let (results, active) = toggle_all_tasks(connection, false, on_error)?;
+            println!("Resumed {} of {} active tasks", results.iter().filter(|result| result.is_ok()).count(), active);
+            first_failure(results)?;
+        }
+        CliCommand::RetryAllTransfers { stalled_only, yes } => {
+            let to_retry = select_file_transfers(&GetFileTransfersCommand::default().execute(connection)?, stalled_only);
+            if to_retry.is_empty() {
+                println!("No file transfers to retry");
+            } else if yes || confirm(&format!("Retry {} file transfers?", to_retry.len()))? {
+                let results = retry_file_transfers(connection, &to_retry, on_error);
+                let failed = results.iter().filter(|result| result.is_err()).count();
+                println!("Retried {} of {} file transfers, {} failed", results.len() - failed, to_retry.len(), failed);
+                first_failure(results)?;
+            }
+        }
         CliCommand::RunBenchmarks => RunBenchmarksCommand::default().execute(connection)?,
-        CliCommand::SetGpuMode { mode, duration } => SetGpuModeCommand::new(mode.into(), duration).execute(connection)?,
-        CliCommand::SetNetworkMode { mode, duration } => SetNetworkModeCommand::new(mode.into(), duration).execute(connection)?,
-        CliCommand::SetRunMode { mode, duration } => SetRunModeCommand::new(mode.into(), duration).execute(connection)?,
-        CliCommand::TaskOp { project_url, name, op } => TaskOpCommand::new(project_url, name, op.into()).execute(connection)?,
-        CliCommand::Version => panic!("Should've never reached this branch"),
+        CliCommand::RunUntilIdle { interval, max_duration } => {
+            let options = PollOptions {
+                overall_timeout: max_duration.map(std::time::Duration::from_secs),
+                interval: std::time::Duration::from_secs(interval),
+            };
+            let (restored, idle) = run_until_idle(connection, &SystemClock, &options)?;
+            if idle {
+                println!("No unfinished tasks left, restored run mode {}", restored);
+            } else {
+                println!("Reached the maximum duration, restored run mode {}", restored);
+            }
+        }
+        CliCommand::SetGpuMode { mode, duration, format } => {
+            let result = SetGpuModeCommand::new(mode.clone().into(), duration).and_then(|mut command| command.execute(connection));
+            report_outcome(&format, "set-gpu-mode", value_name(&mode), result)?;
+        }
+        CliCommand::SetLogFlag { flag, enabled } => {
+            set_log_flag(connection, &flag, enabled)?;
+        }
+        CliCommand::SetNetworkMode { mode, duration, format } => {
+            let result = SetNetworkModeCommand::new(mode.clone().into(), duration).and_then(|mut command| command.execute(connection));
+            report_outcome(&format, "set-network-mode", value_name(&mode), result)?;
+        }
+        CliCommand::SetRunMode { mode, duration, format } => {
+            let result = SetRunModeCommand::new(mode.clone().into(), duration).and_then(|mut command| command.execute(connection));
+            report_outcome(&format, "set-run-mode", value_name(&mode), result)?;
+        }
+        CliCommand::Status => {
+            let cc_status = GetCCStatusCommand::default().execute(connection)?;
+            let tasks = GetResultsCommand::new(false).execute(connection)?;
+            print!("{}", FormattedStatus(&cc_status, count_tasks(&tasks)));
+        }
+        CliCommand::SuspendAllTasks => {
+            let (results, active) = toggle_all_tasks(connection, true, on_error)?;
+            println!("Suspended {} of {} active tasks", results.iter().filter(|result| result.is_ok()).count(), active);
+            first_failure(results)?;
+        }
+        CliCommand::TaskOp { project_url, name, op, format } => {
+            let result = TaskOpCommand::new(project_url, name.clone(), op.into()).execute(connection);
+            report_outcome(&format, "task-op", name, result)?;
+        }
+        CliCommand::SaveState { path } => {
+            let client_version = ExchangeVersionsCommand::default().execute(connection)?;
+            let reply = fetch_raw_reply(connection, &GetStateCommand::default())?;
+            let content = render_saved_state(&reply, &Local::now().to_rfc3339(), client_version)?;
+            std::fs::write(&path, content)?;
+            println!("Saved the state to {}", path.to_string_lossy());
+        }
+        CliCommand::StateDiff { .. } | CliCommand::Version { .. } => panic!("Should've never reached this branch"),
     };
 
     Ok(())
 }
 
+// Returns project url and name of the transfers to retry.
+fn select_file_transfers(file_transfers: &[FileTransfer], stalled_only: bool) -> Vec<(String, String)> {
+    file_transfers
+        .iter()
+        .filter(|file_transfer| !stalled_only || file_transfer.is_stalled())
+        .map(|file_transfer| (file_transfer.project_url.clone(), file_transfer.name.clone()))
+        .collect()
+}
+
+// Edits the client's cc_config.xml in place, so the options joinc doesn't model survive.
+fn set_log_flag(connection: &mut dyn RpcTransport, flag: &str, enabled: bool) -> Result<()> {
+    let client_version = ExchangeVersionsCommand::default().execute(connection)?;
+    let get_cc_config = GetCcConfigCommand::default();
+    get_cc_config.check_client_version(&client_version)?;
+    let mut cc_config = RawCcConfig::new(fetch_raw_reply(connection, &get_cc_config)?)?;
+    cc_config.set_log_flag(flag, enabled)?;
+    SetRawCcConfigCommand::new(cc_config).execute(connection)?;
+    ReadCCConfigCommand::default().execute(connection)
+}
+
+// Retries the given transfers, reporting failures per file. Returns the results of the retried ones.
+fn retry_file_transfers(connection: &mut dyn RpcTransport, file_transfers: &[(String, String)], on_error: OnError) -> Vec<Result<()>> {
+    run_each(file_transfers, on_error, |(project_url, filename)| {
+        let result = FileTransferOpCommand::new_verbatim(project_url.clone(), filename.clone(), FileTransferOp::Retry)
+            .execute(connection);
+        if let Err(err) = &result {
+            eprintln!("Failed to retry {}: {}", filename, err.display());
+        }
+        result
+    })
+}
+
+fn confirm(question: &str) -> Result<bool> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+// The resources are e.g. "1 CPU" or "0.5 CPUs + 1 NVIDIA GPU (device 0)",
+// an empty string means "1 CPU".
+fn uses_gpu(resources: &str) -> bool {
+    resources
+        .split_whitespace()
+        .any(|word| word.eq_ignore_ascii_case("GPU") || word.eq_ignore_ascii_case("GPUs"))
+}
+
+// All set filters have to match.
+struct ProjectFilter {
+    no_more_work: bool,
+    suspended: bool,
+    errors: bool,
+}
+
+impl ProjectFilter {
+    fn matches(&self, project: &Project) -> bool {
+        (!self.no_more_work || requests_no_more_work(project))
+            && (!self.suspended || is_suspended(project))
+            && (!self.errors || has_errors(project))
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct ProjectSummary {
+    user_total_credit: f64,
+    host_total_credit: f64,
+    resource_share: f64,
+}
+
+fn summarize(projects: &[Project]) -> ProjectSummary {
+    projects.iter().fold(ProjectSummary::default(), |summary, project| ProjectSummary {
+        user_total_credit: summary.user_total_credit + project.user_total_credit,
+        host_total_credit: summary.host_total_credit + project.host_total_credit,
+        resource_share: summary.resource_share + project.resource_share,
+    })
+}
+
+#[derive(Debug, PartialEq)]
+struct CreditRate {
+    project_name: String,
+    change: CreditChange,
+}
+
+#[derive(Debug, PartialEq)]
+enum CreditChange {
+    PerHour(f64),
+    Attached,
+    Detached,
+}
+
+// The credit granted to each project between the samples taken `interval` seconds apart.
+// The total credit is compared, as the average credit decays over time.
+fn credit_rates(before: &[Project], after: &[Project], interval: f64) -> Vec<CreditRate> {
+    let find = |projects: &[Project], url: &str| {
+        projects
+            .iter()
+            .position(|project| normalize_project_url(&project.master_url) == normalize_project_url(url))
+    };
+
+    let mut rates: Vec<CreditRate> = after
+        .iter()
+        .map(|project| CreditRate {
+            project_name: project.project_name.clone(),
+            change: match find(before, &project.master_url) {
+                Some(idx) => CreditChange::PerHour(
+                    (project.host_total_credit - before[idx].host_total_credit) * 3600. / interval),
+                None => CreditChange::Attached,
+            },
+        })
+        .collect();
+    rates.extend(before
+        .iter()
+        .filter(|project| find(after, &project.master_url).is_none())
+        .map(|project| CreditRate {
+            project_name: project.project_name.clone(),
+            change: CreditChange::Detached,
+        }));
+    rates
+}
+
+// Each project's share of all resource shares in percent, None if all shares are zero.
+fn resource_share_percentages(projects: &[Project]) -> Vec<Option<f64>> {
+    let total: f64 = projects.iter().map(|project| project.resource_share).sum();
+    projects
+        .iter()
+        .map(|project| Some(100. * project.resource_share / total).filter(|_| total > 0.))
+        .collect()
+}
+
+// Sorts the projects by their scheduling priority, highest first and ties by master URL,
+// returning the index of the first one the client may fetch work for
+fn rank_by_priority<T>(projects: &mut [(Project, T)]) -> Option<usize> {
+    projects.sort_by(|(a, _), (b, _)| {
+        b.sched_priority.total_cmp(&a.sched_priority).then_with(|| a.master_url.cmp(&b.master_url))
+    });
+    projects.iter().position(|(project, _)| {
+        !requests_no_more_work(project) && !is_suspended(project) && !bool::from(project.ended)
+    })
+}
+
+fn requests_no_more_work(project: &Project) -> bool {
+    project.dont_request_more_work.into()
+}
+
+fn is_suspended(project: &Project) -> bool {
+    project.suspended_via_gui.into()
+}
+
+fn has_errors(project: &Project) -> bool {
+    project.nrpc_failures > 0 || project.master_fetch_failures > 0
+}
+
+fn has_priority(msg: &Message, priorities: &[MsgInfo]) -> bool {
+    priorities.is_empty() || priorities.contains(&msg.priority)
+}
+
+fn has_category(notice: &Notice, categories: &[SupportedNoticeCategory]) -> bool {
+    categories.is_empty() || categories.iter().any(|category| category.as_str() == notice.category)
+}
+
+fn matches_body(msg: &Message, body_filter: Option<&BodyFilter>) -> bool {
+    match body_filter {
+        Some(filter) => filter.is_match(&msg.body),
+        None => true,
+    }
+}
+
+#[cfg(feature = "grep")]
+struct BodyFilter(regex::Regex);
+
+#[cfg(feature = "grep")]
+impl BodyFilter {
+    fn new(pattern: &str, case_sensitive: bool) -> std::result::Result<Self, String> {
+        regex::RegexBuilder::new(pattern)
+            .case_insensitive(!case_sensitive)
+            .build()
+            .map(BodyFilter)
+            .map_err(|err| err.to_string())
+    }
+
+    fn is_match(&self, body: &str) -> bool {
+        self.0.is_match(body)
+    }
+}
+
+#[cfg(not(feature = "grep"))]
+struct BodyFilter;
+
+#[cfg(not(feature = "grep"))]
+impl BodyFilter {
+    fn new(_pattern: &str, _case_sensitive: bool) -> std::result::Result<Self, String> {
+        Err("joinccmd was built without feature grep".to_string())
+    }
+
+    fn is_match(&self, _body: &str) -> bool {
+        true
+    }
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct TaskCounts {
+    running: usize,
+    ready: usize,
+    total: usize,
+    // mean fraction done of the running tasks
+    fraction_done: f64,
+}
+
+fn count_tasks(tasks: &[Task]) -> TaskCounts {
+    let is_running = |task: &Task| {
+        task.active_task
+            .as_ref()
+            .is_some_and(|active_task| active_task.scheduler_state == SchedulerState::Scheduled)
+    };
+    let running: Vec<&Task> = tasks.iter().filter(|task| is_running(task)).collect();
+    let fraction_done = running
+        .iter()
+        .filter_map(|task| task.active_task.as_ref())
+        .map(|active_task| active_task.fraction_done)
+        .sum::<f64>();
+
+    TaskCounts {
+        running: running.len(),
+        ready: tasks
+            .iter()
+            .filter(|task| task.state == ResultClientState::FilesDownloaded && !is_running(task))
+            .count(),
+        total: tasks.len(),
+        fraction_done: fraction_of(fraction_done, running.len() as f64),
+    }
+}
+
+fn check_exit_code(cc_status: &CCStatus) -> i32 {
+    if cc_status.is_computing() { 0 } else { 2 }
+}
+
+// why the client holds back a task for its memory needs
+fn memory_warnings(active_task: &ActiveTask) -> Vec<&'static str> {
+    let mut warnings = vec![];
+    if active_task.too_large.into() {
+        warnings.push("task exceeds memory bound");
+    }
+    if active_task.needs_shmem.into() {
+        warnings.push("task is waiting for shared memory");
+    }
+    warnings
+}
+
+fn print_tasks(title: &str, tasks: Vec<Task>, verbose: bool, time_style: TimeStyle) {
+    println!("======== {} ========", title);
+    for (idx, task) in tasks.into_iter().enumerate() {
+        println!("{}) -----------", idx + 1);
+        if verbose {
+            print!("{:#}", task.display_in(time_style));
+        } else {
+            print!("{}", task.display_in(time_style));
+        }
+    }
+}
+
+fn tasks_table(tasks: &[Task], time_style: TimeStyle) -> Table {
+    let mut table = Table::new()
+        .column("name", Align::Left)
+        .column("project URL", Align::Left)
+        .column("state", Align::Left)
+        .column("done", Align::Right)
+        .column("report deadline", Align::Left);
+    for task in tasks {
+        let done = task.active_task.as_ref().map(|at| format!("{:.1}%", at.fraction_done * 100.));
+        table.row(vec![
+            task.name.clone(),
+            task.project_url.clone(),
+            task.state.to_string(),
+            done.unwrap_or_default(),
+            task.report_deadline.display_in(time_style).to_string(),
+        ]);
+    }
+    table
+}
+
+fn project_list_table(projects: &[ProjectListEntry]) -> Table {
+    let mut table = Table::new()
+        .column("name", Align::Left)
+        .column("area", Align::Left)
+        .column("URL", Align::Left);
+    for project in projects {
+        table.row(vec![project.name.clone(), project.general_area.clone(), project.url.clone()]);
+    }
+    table
+}
+
+fn disk_usage_table(disk_usage: &DiskUsageSummary) -> Table {
+    let mut table = Table::new()
+        .column("master URL", Align::Left)
+        .column("disk usage", Align::Right)
+        .column("disk share", Align::Right)
+        .column("of total", Align::Right);
+    for project in &disk_usage.projects {
+        table.row(vec![
+            project.master_url.clone(),
+            Bytes(project.disk_usage).to_string(),
+            Bytes(project.disk_share).to_string(),
+            format!("{:.1}%", fraction_of(project.disk_usage, disk_usage.total) * 100.),
+        ]);
+    }
+    table
+}
+
+// Drops the projects using less than min_bytes and sorts the rest, keeping the client's order if unsorted.
+fn arrange_disk_usage(projects: &mut Vec<DiskUsageSummaryProject>, sort: Option<DiskUsageSort>, min_bytes: Option<f64>) {
+    if let Some(min_bytes) = min_bytes {
+        projects.retain(|project| project.disk_usage >= min_bytes);
+    }
+    match sort {
+        Some(DiskUsageSort::Usage) => projects.sort_by(|a, b| b.disk_usage.total_cmp(&a.disk_usage)),
+        Some(DiskUsageSort::Name) => projects.sort_by(|a, b| a.master_url.cmp(&b.master_url)),
+        None => {}
+    }
+}
+
+// the bytes sent and received by the active tasks in total
+fn network_totals(tasks: &[Task]) -> (f64, f64) {
+    tasks
+        .iter()
+        .filter_map(|task| task.active_task.as_ref())
+        .fold((0., 0.), |(sent, received), active_task| {
+            (sent + active_task.bytes_sent, received + active_task.bytes_received)
+        })
+}
+
+// The tasks not ready to report whose deadline is within the window of seconds
+// or already passed, nearest deadline first.
+fn tasks_at_risk(tasks: Vec<Task>, now: Timestamp, window: f64) -> Vec<Task> {
+    let mut at_risk: Vec<Task> = tasks.into_iter().filter(|task| is_at_risk(task, now, window)).collect();
+    at_risk.sort_by(|a, b| a.report_deadline.0.total_cmp(&b.report_deadline.0));
+    at_risk
+}
+
+fn is_at_risk(task: &Task, now: Timestamp, window: f64) -> bool {
+    !bool::from(task.ready_to_report)
+        && task.report_deadline > Timestamp(0.)
+        && task.report_deadline <= Timestamp(now.0 + window)
+}
+
+// Suspends or resumes all active tasks which aren't in the target state yet.
+// Returns the results of the toggled tasks and the number of active tasks.
+fn toggle_all_tasks(connection: &mut dyn RpcTransport, suspend: bool, on_error: OnError) -> Result<(Vec<Result<()>>, usize)> {
+    let op = if suspend { TaskOp::Suspend } else { TaskOp::Resume };
+    let tasks = GetResultsCommand::new(true).execute(connection)?;
+    let to_toggle: Vec<&Task> = tasks.iter().filter(|task| needs_toggle(task, suspend)).collect();
+
+    let results = run_each(&to_toggle, on_error, |task| {
+        let result = TaskOpCommand::new(task.project_url.clone(), task.name.clone(), op).execute(connection);
+        if let Err(err) = &result {
+            eprintln!("Failed to {} {}: {}", if suspend { "suspend" } else { "resume" }, task.name, err.display());
+        }
+        result
+    });
+
+    Ok((results, tasks.len()))
+}
+
+fn needs_toggle(task: &Task, suspend: bool) -> bool {
+    bool::from(task.suspended_via_gui) != suspend
+}
+
+// Sets the run mode to always until no unfinished tasks are left, then restores the permanent run mode,
+// even if polling failed or timed out. Returns the restored run mode and whether the client became idle.
+fn run_until_idle(connection: &mut dyn RpcTransport, clock: &impl Clock, options: &PollOptions) -> Result<(RunMode, bool)> {
+    let previous = GetCCStatusCommand::default().execute(connection)?.task_mode_perm;
+    let restore = SettableRunMode::try_from(previous)?;
+    SetRunModeCommand::new(SettableRunMode::Always, 0.)?.execute(connection)?;
+
+    let polled = poll_until(options, clock, || {
+        let tasks = GetResultsCommand::new(false).execute(connection)?;
+        Ok(Some(()).filter(|_| is_idle(&tasks)))
+    });
+
+    SetRunModeCommand::new(restore, 0.)?.execute(connection)?;
+    polled.map(|idle| (previous, idle.is_some()))
+}
+
+// whether none of the tasks is left to compute, finished tasks may still be uploaded or reported
+fn is_idle(tasks: &[Task]) -> bool {
+    tasks.iter().all(|task| task.state > ResultClientState::FilesDownloaded)
+}
+
+// ----- config file -----
+
+#[derive(Debug, Default, Deserialize, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+struct Profile {
+    host: Option<String>,
+    port: Option<u16>,
+    passwd: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Config {
+    default: Profile,
+    host: std::collections::HashMap<String, Profile>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Target {
+    host: String,
+    port: u16,
+    passwd: Option<String>,
+}
+
+// --no-auth is passed on as an empty password, taking precedence over the config file's ones
+fn cli_passwd(cli: &Cli) -> Option<String> {
+    if cli.no_auth {
+        Some(String::new())
+    } else {
+        cli.passwd.clone()
+    }
+}
+
+// The cli's flags take precedence over the selected profile,
+// which takes precedence over the default section.
+// An empty password means the client has none, so authenticating is skipped.
+fn resolve_target(cli: Profile, config: &Config, profile: Option<&str>) -> std::result::Result<Target, String> {
+    let selected = match profile {
+        Some(name) => Some(config.host.get(name).ok_or_else(|| format!("Unknown profile: {}", name))?),
+        None => None,
+    };
+    let pick = |get: fn(&Profile) -> Option<&String>| {
+        get(&cli).or_else(|| selected.and_then(get)).or_else(|| get(&config.default)).cloned()
+    };
+
+    Ok(Target {
+        host: pick(|p| p.host.as_ref()).unwrap_or_else(|| "localhost".to_string()),
+        port: cli.port
+            .or_else(|| selected.and_then(|p| p.port))
+            .or(config.default.port)
+            .unwrap_or(connection::DEFAULT_PORT),
+        passwd: pick(|p| p.passwd.as_ref()).filter(|passwd| !passwd.is_empty()),
+    })
+}
+
+fn config_path() -> Option<std::path::PathBuf> {
+    std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(std::path::PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| std::path::Path::new(&home).join(".config")))
+        .map(|dir| dir.join("joinc").join("config.toml"))
+}
+
+#[cfg(feature = "config")]
+fn load_config() -> std::result::Result<Config, String> {
+    let Some(path) = config_path().filter(|path| path.exists()) else {
+        return Ok(Config::default());
+    };
+    let content = std::fs::read_to_string(&path).map_err(|err| format!("{}: {}", path.to_string_lossy(), err))?;
+    toml::from_str(&content).map_err(|err| format!("{}: {}", path.to_string_lossy(), err))
+}
+
+#[cfg(not(feature = "config"))]
+fn load_config() -> std::result::Result<Config, String> {
+    match config_path().filter(|path| path.exists()) {
+        Some(path) => Err(format!("{}: joinccmd was built without feature config", path.to_string_lossy())),
+        None => Ok(Config::default()),
+    }
+}
+
 // ----- helpers for parsing cli parameters -----
 
+#[derive(Clone, Copy, PartialEq, ValueEnum)]
+enum DiskUsageSort {
+    Usage,
+    Name,
+}
+
 #[derive(Clone, PartialEq, ValueEnum)]
 enum SupportedFileTransferOp {
     Abort,
@@ -242,9 +1448,41 @@ enum SupportedFileTransferOp {
 
 impl From<SupportedFileTransferOp> for FileTransferOp {
     fn from(op: SupportedFileTransferOp) -> Self {
-        match op {
-            SupportedFileTransferOp::Abort => FileTransferOp::Abort,
-            SupportedFileTransferOp::Retry => FileTransferOp::Retry,
+        parse_value(&op)
+    }
+}
+
+#[derive(Clone, PartialEq, ValueEnum)]
+enum SupportedNoticeCategory {
+    News,
+    Client,
+    Scheduler,
+}
+
+impl SupportedNoticeCategory {
+    // the category as named by the client
+    fn as_str(&self) -> &'static str {
+        match self {
+            SupportedNoticeCategory::News => "news",
+            SupportedNoticeCategory::Client => "client",
+            SupportedNoticeCategory::Scheduler => "scheduler",
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, ValueEnum)]
+enum SupportedMsgPriority {
+    Low,
+    User,
+    Error,
+}
+
+impl From<SupportedMsgPriority> for MsgInfo {
+    fn from(priority: SupportedMsgPriority) -> Self {
+        match priority {
+            SupportedMsgPriority::Low => MsgInfo::Info,
+            SupportedMsgPriority::User => MsgInfo::UserAlert,
+            SupportedMsgPriority::Error => MsgInfo::InternalError,
         }
     }
 }
@@ -264,17 +1502,7 @@ enum SupportedProjectOp {
 
 impl From<SupportedProjectOp> for ProjectOp {
     fn from(op: SupportedProjectOp) -> Self {
-        match op {
-            SupportedProjectOp::Allowmorework => ProjectOp::Allowmorework,
-            SupportedProjectOp::Detach => ProjectOp::Detach,
-            SupportedProjectOp::DetachWhenDone => ProjectOp::DetachWhenDone,
-            SupportedProjectOp::DontDetachWhenDone => ProjectOp::DontDetachWhenDone,
-            SupportedProjectOp::Nomorework => ProjectOp::Nomorework,
-            SupportedProjectOp::Reset => ProjectOp::Reset,
-            SupportedProjectOp::Resume => ProjectOp::Resume,
-            SupportedProjectOp::Suspend => ProjectOp::Suspend,
-            SupportedProjectOp::Update => ProjectOp::Update,
-        }
+        parse_value(&op)
     }
 }
 
@@ -285,13 +1513,9 @@ enum SupportedRunMode {
     Never,
 }
 
-impl From<SupportedRunMode> for RunMode {
+impl From<SupportedRunMode> for SettableRunMode {
     fn from(mode: SupportedRunMode) -> Self {
-        match mode {
-            SupportedRunMode::Always => RunMode::Always,
-            SupportedRunMode::Auto => RunMode::Auto,
-            SupportedRunMode::Never => RunMode::Never,
-        }
+        parse_value(&mode)
     }
 }
 
@@ -304,35 +1528,343 @@ enum SupportedTaskOp {
 
 impl From<SupportedTaskOp> for TaskOp {
     fn from(op: SupportedTaskOp) -> Self {
-        match op {
-            SupportedTaskOp::Abort => TaskOp::Abort,
-            SupportedTaskOp::Resume => TaskOp::Resume,
-            SupportedTaskOp::Suspend => TaskOp::Suspend,
+        parse_value(&op)
+    }
+}
+
+// the name of the value on the command line
+fn value_name<V: ValueEnum>(value: &V) -> String {
+    value.to_possible_value().expect("no skipped values").get_name().to_string()
+}
+
+// The cli names of the values are the stable names of the library types.
+fn parse_value<V: ValueEnum, T: FromStr<Err = Error>>(value: &V) -> T {
+    let name = value.to_possible_value().expect("no skipped values");
+    name.get_name().parse().unwrap_or_else(|err: Error| panic!("{}", err.display()))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct StateSections(u8);
+
+impl StateSections {
+    const PROJECTS: Self = Self(1 << 0);
+    const TASKS: Self = Self(1 << 1);
+    const HOST: Self = Self(1 << 2);
+    const APPS: Self = Self(1 << 3);
+    const TIME_STATS: Self = Self(1 << 4);
+    const NET_STATS: Self = Self(1 << 5);
+    const ALL: Self = Self((1 << 6) - 1);
+
+    fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for StateSections {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+fn parse_mode_duration(value: &str) -> std::result::Result<f64, String> {
+    let duration: f64 = value.parse().map_err(|err| format!("{}", err))?;
+    if duration.is_finite() && duration >= 0. {
+        Ok(duration)
+    } else {
+        Err("expected a number of seconds >= 0".to_string())
+    }
+}
+
+fn parse_state_sections(csv: &str) -> std::result::Result<StateSections, String> {
+    csv.split(',').map(str::trim).try_fold(StateSections(0), |sections, name| {
+        Ok(sections | match name {
+            "projects" => StateSections::PROJECTS,
+            "tasks" => StateSections::TASKS,
+            "host" => StateSections::HOST,
+            "apps" => StateSections::APPS,
+            "time-stats" => StateSections::TIME_STATS,
+            "net-stats" => StateSections::NET_STATS,
+            _ => return Err(format!("unknown section: {}", name)),
+        })
+    })
+}
+
+// ----- notices feed -----
+
+#[derive(Clone, PartialEq, ValueEnum)]
+enum NoticeFormat {
+    Text,
+    Rss,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "channel")]
+struct RssChannel {
+    title: &'static str,
+    link: &'static str,
+    description: &'static str,
+    item: Vec<RssItem>,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "item")]
+struct RssItem {
+    title: String,
+    link: Option<String>,
+    description: String,
+    category: Option<String>,
+    #[serde(rename = "pubDate")]
+    pub_date: Option<String>,
+}
+
+impl From<&Notice> for RssItem {
+    fn from(notice: &Notice) -> Self {
+        let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+        let title = match notice.project_name.as_str() {
+            "" => notice.title.trim().to_string(),
+            project_name => format!("{}: {}", project_name, notice.title.trim()),
+        };
+        RssItem {
+            title,
+            link: non_empty(&notice.link),
+            description: notice.description.trim().to_string(),
+            category: non_empty(&notice.category),
+            pub_date: Some(notice.create_time.0)
+                .filter(|&t| t > 0.)
+                .and_then(|t| DateTime::from_timestamp(t as i64, 0))
+                .map(|time| time.to_rfc2822()),
         }
     }
 }
 
-// ----- unit conversions -----
+// An RSS 2.0 document of the notices, in the given order.
+fn notices_feed(notices: &[Notice]) -> Result<String> {
+    let channel = RssChannel {
+        title: "BOINC notices",
+        link: "https://boinc.berkeley.edu/",
+        description: "The notices of the BOINC client",
+        item: notices.iter().map(RssItem::from).collect(),
+    };
+    let channel = libjoincserde::to_vec(&channel)?;
+    let channel = String::from_utf8_lossy(&channel);
+    let feed = libjoincserde::pretty_print(&format!("<rss version=\"2.0\">{}</rss>", channel))?;
+    Ok(format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{}\n", feed))
+}
+
+// ----- state diff -----
+
+#[derive(Debug, PartialEq)]
+enum StateChange {
+    ProjectAttached(String),
+    ProjectDetached(String),
+    ProjectGainedWork(String),
+    ProjectRanOutOfWork(String),
+    ProjectSuspended(String),
+    ProjectResumed(String),
+    TaskAdded(String),
+    TaskRemoved(String),
+    TaskStarted(String),
+    TaskFinished(String, ResultClientState),
+    TaskStateChanged(String, ResultClientState, ResultClientState),
+}
+
+fn render_saved_state(reply: &str, saved_at: &str, client_version: Version) -> Result<String> {
+    Ok(format!("<!-- saved by joinccmd {} at {} from client {} -->\n{}\n",
+        JOINCCMD_VERSION, saved_at, client_version, libjoincserde::pretty_print(reply)?))
+}
 
-fn to_mibi(d: f64) -> f64 {
-    d / (1024. * 1024.)
+fn load_state(path: &std::path::Path) -> std::result::Result<CCState, String> {
+    let xml = std::fs::read_to_string(path).map_err(|err| format!("{}: {}", path.to_string_lossy(), err))?;
+    GetStateCommand::parse(&xml).map_err(|err| format!("{}: {}", path.to_string_lossy(), err.display()))
 }
 
+// The changes from state a to state b, the projects' changes first.
+fn diff_states(a: &CCState, b: &CCState) -> Vec<StateChange> {
+    let has_work = |state: &CCState, url: &str| {
+        let url = normalize_project_url(url);
+        state.tasks.iter().any(|task| normalize_project_url(&task.project_url) == url)
+    };
+    let is_started = |task: &Task| task.active_task.is_some();
+    let is_computing = |task: &Task| task.state >= ResultClientState::New && task.state <= ResultClientState::FilesDownloaded;
+
+    let mut changes = vec![];
+    for project in &b.projects {
+        let name = project.project_name.clone();
+        let Some(before) = find_project(a, &project.master_url) else {
+            changes.push(StateChange::ProjectAttached(name));
+            continue;
+        };
+        match (has_work(a, &project.master_url), has_work(b, &project.master_url)) {
+            (false, true) => changes.push(StateChange::ProjectGainedWork(name.clone())),
+            (true, false) => changes.push(StateChange::ProjectRanOutOfWork(name.clone())),
+            _ => (),
+        }
+        match (is_suspended(before), is_suspended(project)) {
+            (false, true) => changes.push(StateChange::ProjectSuspended(name)),
+            (true, false) => changes.push(StateChange::ProjectResumed(name)),
+            _ => (),
+        }
+    }
+    changes.extend(a.projects
+        .iter()
+        .filter(|project| find_project(b, &project.master_url).is_none())
+        .map(|project| StateChange::ProjectDetached(project.project_name.clone())));
+
+    for task in &b.tasks {
+        let name = task.name.clone();
+        let Some(before) = find_task(a, &task.name) else {
+            changes.push(StateChange::TaskAdded(name));
+            continue;
+        };
+        if is_computing(before) && !is_computing(task) {
+            changes.push(StateChange::TaskFinished(name, task.state));
+        } else if before.state != task.state {
+            changes.push(StateChange::TaskStateChanged(name, before.state, task.state));
+        } else if !is_started(before) && is_started(task) {
+            changes.push(StateChange::TaskStarted(name));
+        }
+    }
+    changes.extend(a.tasks
+        .iter()
+        .filter(|task| find_task(b, &task.name).is_none())
+        .map(|task| StateChange::TaskRemoved(task.name.clone())));
+
+    changes
+}
+
+fn find_project<'a>(state: &'a CCState, url: &str) -> Option<&'a Project> {
+    let url = normalize_project_url(url);
+    state.projects.iter().find(|project| normalize_project_url(&project.master_url) == url)
+}
+
+fn find_task<'a>(state: &'a CCState, name: &str) -> Option<&'a Task> {
+    state.tasks.iter().find(|task| task.name == name)
+}
+
+// ----- machine-readable output -----
+
+#[derive(Clone, PartialEq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+// The outcome of an operation changing the client's state, e.g. project-op.
+#[derive(Debug, Serialize)]
+struct CommandOutcome {
+    ok: bool,
+    command: &'static str,
+    target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl CommandOutcome {
+    fn new(command: &'static str, target: String, result: &Result<()>) -> Self {
+        Self {
+            ok: result.is_ok(),
+            command,
+            target,
+            error: result.as_ref().err().map(|err| err.display().to_string()),
+        }
+    }
+}
+
+// Prints the outcome under --format json, the error is passed on for the exit code either way.
+fn report_outcome(format: &OutputFormat, command: &'static str, target: String, result: Result<()>) -> Result<()> {
+    if *format == OutputFormat::Json {
+        println!("{}", serde_json::to_string(&CommandOutcome::new(command, target, &result)).expect("outcome is serializable"));
+    }
+    result
+}
+
+fn version_json() -> serde_json::Value {
+    serde_json::json!({
+        "joinccmd": JOINCCMD_VERSION,
+        "libjoinc": libjoinc::VERSION,
+    })
+}
+
+fn host_info_json(host_info: &HostInfo) -> serde_json::Value {
+    serde_json::json!({
+        "domain_name": host_info.domain_name,
+        "ip_addr": host_info.ip_addr,
+        "timezone": host_info.timezone,
+        "os_name": host_info.os_name,
+        "os_version": host_info.os_version,
+        "cpu_vendor": host_info.p_vendor,
+        "cpu_model": host_info.p_model,
+        "cpu_count": host_info.p_ncpus,
+        "cpu_gflops": host_info.cpu_gflops(),
+        "cpu_giops": host_info.cpu_giops(),
+        "memory_gb": host_info.memory_gb(),
+        "swap_gb": host_info.swap_gb(),
+        "disk_total_gb": host_info.disk_total_gb(),
+        "disk_free_gb": host_info.disk_free_gb(),
+    })
+}
+
+fn write_json_lines<W, T>(writer: &mut W, items: &[T]) -> io::Result<()>
+where
+    W: Write,
+    T: Serialize,
+{
+    for item in items {
+        serde_json::to_writer(&mut *writer, item)?;
+        writeln!(writer)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+// ----- unit conversions -----
+
 fn to_gflops(f: f64) -> f64 {
     f / 1e9
 }
 
+// e.g. 512 B, 4.0 KiB, 1.5 GiB or 512 MiB, values from 100 on without decimals
+fn format_bytes(n: f64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    if n.abs() < 1024. {
+        return format!("{:.0} B", n);
+    }
+    let mut value = n;
+    let mut unit = UNITS[0];
+    for next in UNITS {
+        value /= 1024.;
+        unit = next;
+        if value.abs() < 1024. {
+            break;
+        }
+    }
+    if value.abs() >= 100. {
+        format!("{:.0} {}", value, unit)
+    } else {
+        format!("{:.1} {}", value, unit)
+    }
+}
+
 // ----- helpers for displaying -----
 
-struct Displayable<T>(T);
+// The value and the style of the timestamps it shows, if any.
+struct Displayable<T>(T, TimeStyle);
 
 trait Display<T> {
     fn display(self) -> Displayable<T>;
+    fn display_in(self, time_style: TimeStyle) -> Displayable<T>;
 }
 
 impl<T> Display<T> for T {
     fn display(self) -> Displayable<T> {
-        Displayable(self)
+        self.display_in(TimeStyle::default())
+    }
+
+    fn display_in(self, time_style: TimeStyle) -> Displayable<T> {
+        Displayable(self, time_style)
     }
 }
 
@@ -345,37 +1877,50 @@ impl fmt::Display for Displayable<bool> {
     }
 }
 
-struct FormattedTimestamp {
-    timestamp: Timestamp,
-    format: &'static str,
+#[derive(Clone, Copy, ValueEnum)]
+enum TimeFormat {
+    /// The local time, e.g. 03-Feb-2024 04:05:06
+    Local,
+    /// RFC 3339 in UTC, e.g. 2024-02-03T04:05:06Z
+    Iso8601,
+    /// The seconds since the epoch
+    Epoch,
 }
 
-impl FormattedTimestamp {
-    fn new(timestamp: Timestamp) -> Self {
-        Self {
-            timestamp,
-            format: "%c",
+impl From<TimeFormat> for TimeStyle {
+    fn from(format: TimeFormat) -> Self {
+        match format {
+            TimeFormat::Local => TimeStyle::Absolute,
+            TimeFormat::Iso8601 => TimeStyle::Iso8601,
+            TimeFormat::Epoch => TimeStyle::Epoch,
         }
     }
+}
 
-    fn with_format(timestamp: Timestamp, format: &'static str) -> Self {
-        Self { timestamp, format }
+impl fmt::Display for Displayable<Timestamp> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&format_timestamp(self.0, self.1).unwrap_or("---".to_string()))
     }
 }
 
-impl fmt::Display for FormattedTimestamp {
+// in seconds, e.g. 1h 02m 03s
+struct FormattedDuration(f64);
+
+impl fmt::Display for FormattedDuration {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}",
-            &Some(self.timestamp.0)
-                .filter(|&t| t > 0.)
-                .and_then(|t| NaiveDateTime::from_timestamp_opt(t as i64, 0))
-                .map(|ndt| Local
-                    .from_utc_datetime(&ndt)
-                    .with_timezone(&Local)
-                    .format(self.format)
-                    .to_string())
-                .unwrap_or("---".to_string())
-        )
+        if self.0.is_nan() || self.0 <= 0. {
+            return f.write_str("---");
+        }
+
+        let secs = self.0.round() as u64;
+        let (hours, mins, secs) = (secs / 3600, secs / 60 % 60, secs % 60);
+        if hours > 0 {
+            write!(f, "{}h {:02}m {:02}s", hours, mins, secs)
+        } else if mins > 0 {
+            write!(f, "{}m {:02}s", mins, secs)
+        } else {
+            write!(f, "{}s", secs)
+        }
     }
 }
 
@@ -395,11 +1940,49 @@ impl fmt::Display for FormattedCCState<'_> {
     }
 }
 
-struct Usage(f64);
+struct FormattedStatus<'a>(&'a CCStatus, TaskCounts);
+
+impl fmt::Display for FormattedStatus<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (cc_status, counts) = (self.0, &self.1);
+        let suspended = |reason: SuspendReason| match reason {
+            SuspendReason::NotSuspended => String::new(),
+            _ => format!(" (suspended: {})", reason),
+        };
+
+        writeln!(f, "run mode: {}{}", cc_status.task_mode, suspended(cc_status.task_suspend_reason))?;
+        writeln!(f, "GPU mode: {}{}", cc_status.gpu_mode, suspended(cc_status.gpu_suspend_reason))?;
+        writeln!(f, "network mode: {}{}", cc_status.network_mode, suspended(cc_status.network_suspend_reason))?;
+        writeln!(f, "tasks: {} running, {} ready, {} total", counts.running, counts.ready, counts.total)?;
+        if counts.running > 0 {
+            writeln!(f, "running tasks done: {:.1}%", counts.fraction_done * 100.)?;
+        }
+        Ok(())
+    }
+}
+
+const BAR_WIDTH: usize = 20;
 
-impl fmt::Display for Usage {
+fn render_bar(fraction: f64, width: usize) -> String {
+    let fraction = if fraction.is_nan() { 0. } else { fraction.clamp(0., 1.) };
+    let filled = (fraction * width as f64).round() as usize;
+    format!("[{}{}]", "#".repeat(filled), "-".repeat(width - filled))
+}
+
+fn fraction_of(part: f64, total: f64) -> f64 {
+    if total > 0. { part / total } else { 0. }
+}
+
+// A size in bytes, the alternate flag ({:#}) shows the exact number of bytes.
+struct Bytes(f64);
+
+impl fmt::Display for Bytes {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{:.2}MB", self.0 / (1024. * 1024.))
+        if f.alternate() {
+            write!(f, "{:.6}", self.0)
+        } else {
+            f.write_str(&format_bytes(self.0))
+        }
     }
 }
 
@@ -414,19 +1997,37 @@ impl fmt::Display for Displayable<CCStatus> {
                 self.0.gpu_mode, self.0.gpu_mode_delay, self.0.gpu_mode_perm, self.0.gpu_suspend_reason))?;
         write!(f, "{}", FormattedCCState("Network",
                 self.0.network_mode, self.0.network_mode_delay, self.0.network_mode_perm, self.0.network_suspend_reason))?;
+        // explain a manager behaving oddly
+        let warnings = [
+            (self.0.ams_password_error, "the account manager password is wrong"),
+            (self.0.disallow_attach, "attaching projects is disallowed"),
+            (self.0.manager_must_quit, "the manager must quit"),
+            (self.0.simple_gui_only, "the manager must show the simple GUI only"),
+        ];
+        for (_, warning) in warnings.iter().filter(|(flag, _)| (*flag).into()) {
+            writeln!(f, "warning: {}", warning)?;
+        }
         Ok(())
     }
 }
 
 impl fmt::Display for Displayable<DiskUsageSummary> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "total: {}", Usage(self.0.total))?;
-        writeln!(f, "free: {}", Usage(self.0.free))?;
+        writeln!(f, "total: {}", Bytes(self.0.total))?;
+        writeln!(f, "free: {}", Bytes(self.0.free))?;
+        writeln!(f, "used by BOINC: {}", Bytes(self.0.boinc))?;
+        writeln!(f, "allowed for BOINC: {}", Bytes(self.0.allowed))?;
 
         for (idx, project) in self.0.projects.iter().enumerate() {
             writeln!(f, "{}) -----------", idx + 1)?;
             writeln!(f, "{INDENT3}master URL: {}", project.master_url)?;
-            writeln!(f, "{INDENT3}disk usage: {}", Usage(project.disk_usage))?;
+            writeln!(f, "{INDENT3}disk usage: {}", Bytes(project.disk_usage))?;
+            writeln!(f, "{INDENT3}disk share: {}", Bytes(project.disk_share))?;
+            // the alternate flag ({:#}) adds the share of the total disk space
+            if f.alternate() {
+                let fraction = fraction_of(project.disk_usage, self.0.total);
+                writeln!(f, "{INDENT3}share of total: {:5.1}% {}", fraction * 100., render_bar(fraction, BAR_WIDTH))?;
+            }
         }
 
         Ok(())
@@ -435,10 +2036,18 @@ impl fmt::Display for Displayable<DiskUsageSummary> {
 
 impl fmt::Display for Displayable<Error> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match &self.0 {
+        (&self.0).display().fmt(f)
+    }
+}
+
+impl fmt::Display for Displayable<&Error> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
             Error::Client(err) => write!(f, "Client error: {}.", err),
             Error::Deserialization(serde_err) => write!(f, "Deserialization error: {}.", serde_err),
+            Error::InvalidArgument(err) => write!(f, "Invalid argument: {}.", err),
             Error::Io(io_err) => write!(f, "IO error: {}.", io_err),
+            Error::Rejected(err, error_num) => write!(f, "Client error: {} (error_num {}).", err, error_num),
             Error::Rpc(rpc_err) => write!(f, "RPC error: {}.", rpc_err),
             Error::Unauthorized => write!(f, "Unauthorized, please set the password via --passwd <PASSWD>."),
         }
@@ -470,12 +2079,25 @@ impl fmt::Display for Displayable<FileTransfer> {
         writeln!(f, "{INDENT3}direction: {}", direction)?;
         writeln!(f, "{INDENT3}sticky: {}", self.0.sticky)?;
         writeln!(f, "{INDENT3}xfer active: {}", is_active.display())?;
+        if self.0.status.0 != 0 {
+            writeln!(f, "{INDENT3}status: {}", self.0.status)?;
+        }
+        if self.0.project_backoff.0 > 0. {
+            writeln!(f, "{INDENT3}project backoff: {}", FormattedDuration(self.0.project_backoff.0))?;
+        }
         writeln!(f, "{INDENT3}time_so_far: {:.6}", time_so_far)?;
         if is_active {
             writeln!(f, "{INDENT3}estimated_xfer_time_remaining: {:.6}", estimated_xfer_time_remaining)?;
         }
         writeln!(f, "{INDENT3}bytes_xferred: {:.6}", bytes_xferred)?;
         writeln!(f, "{INDENT3}xfer_speed: {:.6}", xfer_speed)?;
+        match self.0.fraction_done() {
+            Some(fraction) => writeln!(f, "{INDENT3}progress: {:.2}%", fraction * 100.)?,
+            None => writeln!(f, "{INDENT3}progress: unknown")?,
+        }
+        if is_active {
+            writeln!(f, "{INDENT3}ETA: {}", FormattedDuration(estimated_xfer_time_remaining))?;
+        }
 
         Ok(())
     }
@@ -484,17 +2106,19 @@ impl fmt::Display for Displayable<FileTransfer> {
 impl fmt::Display for Displayable<&Coprocs> {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
       for (index, coproc) in self.0.amds.iter().enumerate() {
-          writeln!(f, "{INDENT2}AMD GPU: {} (CAL version {}, {}MB, {:.0}MB available, {:.0} GFLOPS peak)",
-              coproc.name, coproc.version, coproc.local_ram, to_mibi(coproc.available_ram), to_gflops(coproc.peak_flops))?;
+          // CAL reports the local RAM in MiB
+          let local_ram = Bytes(f64::from(coproc.local_ram) * 1024. * 1024.);
+          writeln!(f, "{INDENT2}AMD GPU: {} (CAL version {}, {}, {} available, {:.0} GFLOPS peak)",
+              coproc.name, coproc.version, local_ram, Bytes(coproc.available_ram), to_gflops(coproc.peak_flops))?;
 
           if coproc.count > 1 {
               writeln!(f, "{INDENT4}Count: {}", coproc.count)?;
           }
 
           if let Some(opencl) = &coproc.opencl {
-              writeln!(f, "{INDENT4}OpenCL: AMD {}: {:.64} (driver version {:.64}, device version {:.64}, {:.0}MB, {:.0}MB available, {:.0} GFLOPS peak)",
+              writeln!(f, "{INDENT4}OpenCL: AMD {}: {:.64} (driver version {:.64}, device version {:.64}, {}, {} available, {:.0} GFLOPS peak)",
                   index, opencl.name, opencl.opencl_driver_version, opencl.opencl_device_version.trim(),
-                  to_mibi(opencl.global_mem_size), to_mibi(coproc.available_ram), to_gflops(coproc.peak_flops))?;
+                  Bytes(opencl.global_mem_size), Bytes(coproc.available_ram), to_gflops(coproc.peak_flops))?;
           }
       }
 
@@ -502,6 +2126,43 @@ impl fmt::Display for Displayable<&Coprocs> {
   }
 }
 
+impl fmt::Display for Displayable<App> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{INDENT3}name: {}", self.0.name)?;
+        writeln!(f, "{INDENT3}user friendly name: {}", self.0.user_friendly_name)?;
+        writeln!(f, "{INDENT3}non CPU intensive: {}", self.0.non_cpu_intensive)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Displayable<NetStats> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{INDENT2}upload: {:.2} B/s, average {:.2} B/s", self.0.bwup, self.0.avg_up)?;
+        writeln!(f, "{INDENT2}download: {:.2} B/s, average {:.2} B/s", self.0.bwdown, self.0.avg_down)?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for Displayable<TimeStats> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stats = &self.0;
+        writeln!(f, "{INDENT2}on_frac: {:.6}", stats.on_frac)?;
+        writeln!(f, "{INDENT2}connected_frac: {:.6}", stats.connected_frac)?;
+        writeln!(f, "{INDENT2}cpu_and_network_available_frac: {:.6}", stats.cpu_and_network_available_frac)?;
+        writeln!(f, "{INDENT2}active_frac: {:.6}", stats.active_frac)?;
+        writeln!(f, "{INDENT2}gpu_active_frac: {:.6}", stats.gpu_active_frac)?;
+        writeln!(f, "{INDENT2}client_start_time: {}", stats.client_start_time.display_in(self.1))?;
+        writeln!(f, "{INDENT2}previous_uptime: {:.6}", stats.previous_uptime.0)?;
+        writeln!(f, "{INDENT2}session_active_duration: {:.6}", stats.session_active_duration.0)?;
+        writeln!(f, "{INDENT2}session_gpu_active_duration: {:.6}", stats.session_gpu_active_duration.0)?;
+        writeln!(f, "{INDENT2}total_start_time: {}", stats.total_start_time.display_in(self.1))?;
+        writeln!(f, "{INDENT2}total_duration: {:.6}", stats.total_duration.0)?;
+        writeln!(f, "{INDENT2}total_active_duration: {:.6}", stats.total_active_duration.0)?;
+        writeln!(f, "{INDENT2}total_gpu_active_duration: {:.6}", stats.total_gpu_active_duration.0)?;
+        Ok(())
+    }
+}
+
 impl fmt::Display for Displayable<HostInfo> {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
       writeln!(f, "{INDENT2}timezone: {}", self.0.timezone)?;
@@ -515,11 +2176,14 @@ impl fmt::Display for Displayable<HostInfo> {
       writeln!(f, "{INDENT2}CPU mem BW: {:.6}", self.0.p_membw)?;
       writeln!(f, "{INDENT2}OS name: {}", self.0.os_name)?;
       writeln!(f, "{INDENT2}OS version: {}", self.0.os_version)?;
-      writeln!(f, "{INDENT2}mem size: {:.6}", self.0.m_nbytes)?;
-      writeln!(f, "{INDENT2}cache size: {:.6}", self.0.m_cache)?;
-      writeln!(f, "{INDENT2}swap size: {:.6}", self.0.m_swap)?;
-      writeln!(f, "{INDENT2}disk size: {:.6}", self.0.d_total)?;
-      writeln!(f, "{INDENT2}disk free: {:.6}", self.0.d_free)?;
+      // the alternate flag ({:#}) is passed on to show the exact sizes
+      let exact = f.alternate();
+      let bytes = |n: f64| if exact { format!("{:#}", Bytes(n)) } else { Bytes(n).to_string() };
+      writeln!(f, "{INDENT2}mem size: {}", bytes(self.0.m_nbytes))?;
+      writeln!(f, "{INDENT2}cache size: {}", bytes(self.0.m_cache))?;
+      writeln!(f, "{INDENT2}swap size: {}", bytes(self.0.m_swap))?;
+      writeln!(f, "{INDENT2}disk size: {}", bytes(self.0.d_total))?;
+      writeln!(f, "{INDENT2}disk free: {}", bytes(self.0.d_free))?;
       write!(f, "{}", (&self.0.coprocs).display())?;
       Ok(())
     }
@@ -529,7 +2193,7 @@ impl fmt::Display for Displayable<Message> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}: {} ({}) [{}] {}",
             self.0.seqno,
-            FormattedTimestamp::with_format(self.0.timestamp, "%d-%b-%Y %H:%M:%S"),
+            self.0.timestamp.display_in(self.1),
             self.0.priority,
             self.0.project,
             self.0.body.trim()
@@ -539,23 +2203,85 @@ impl fmt::Display for Displayable<Message> {
 
 impl fmt::Display for Displayable<Notice> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}: ({}) {}",
-            self.0.seqno,
-            FormattedTimestamp::with_format(self.0.create_time, "%d-%b-%Y %H:%M:%S"),
-            self.0.description.trim()
-        )
+        let notice = &self.0;
+
+        write!(f, "{}: ({}) [{}]",
+            notice.seqno,
+            notice.create_time.display_in(self.1),
+            notice.category
+        )?;
+        if !notice.project_name.is_empty() {
+            write!(f, " {}:", notice.project_name)?;
+        }
+        if notice.is_private.into() {
+            write!(f, " (private)")?;
+        }
+        if notice.title.trim().is_empty() {
+            write!(f, " {}", notice.description.trim())?;
+        } else {
+            write!(f, " {}", notice.title.trim())?;
+            write!(f, "\n{INDENT3}{}", notice.description.trim())?;
+        }
+        if !notice.link.is_empty() {
+            write!(f, "\n{INDENT3}link: {}", notice.link)?;
+        }
+        Ok(())
     }
 }
 
-impl fmt::Display for Displayable<Project> {
+impl fmt::Display for Displayable<ProjectListEntry> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let project = &self.0;
 
+        writeln!(f, "{INDENT3}name: {}", project.name)?;
+        writeln!(f, "{INDENT3}URL: {}", project.url)?;
+        writeln!(f, "{INDENT3}area: {} / {}", project.general_area, project.specific_area)?;
+        writeln!(f, "{INDENT3}platforms: {}", match project.platforms.0.is_empty() {
+            true => "(any)".to_string(),
+            false => project.platforms.0.join(", "),
+        })?;
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Displayable<ProjectSummary> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let summary = &self.0;
+
+        writeln!(f, "{INDENT3}total user_total_credit: {:.6}", summary.user_total_credit)?;
+        writeln!(f, "{INDENT3}total host_total_credit: {:.6}", summary.host_total_credit)?;
+        writeln!(f, "{INDENT3}total resource share: {:.6}", summary.resource_share)?;
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Displayable<CreditRate> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rate = &self.0;
+
+        match rate.change {
+            CreditChange::PerHour(credit) => writeln!(f, "{}: {:.2} credits/hour", rate.project_name, credit),
+            CreditChange::Attached => writeln!(f, "{}: attached while sampling", rate.project_name),
+            CreditChange::Detached => writeln!(f, "{}: detached while sampling", rate.project_name),
+        }
+    }
+}
+
+// A project along with its resource share in percent
+impl fmt::Display for Displayable<(Project, Option<f64>)> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (project, share) = &self.0;
+
         writeln!(f, "{INDENT3}name: {}", project.project_name)?;
         writeln!(f, "{INDENT3}master URL: {}", project.master_url)?;
         writeln!(f, "{INDENT3}user_name: {}", project.user_name)?;
         writeln!(f, "{INDENT3}team_name: {}", project.team_name)?;
-        writeln!(f, "{INDENT3}resource share: {:.6}", project.resource_share)?;
+        match share {
+            Some(share) => writeln!(f, "{INDENT3}resource share: {} ({:.1}%)", project.resource_share, share)?,
+            None => writeln!(f, "{INDENT3}resource share: {}", project.resource_share)?,
+        }
         writeln!(f, "{INDENT3}user_total_credit: {:.6}", project.user_total_credit)?;
         writeln!(f, "{INDENT3}user_expavg_credit: {:.6}", project.user_expavg_credit)?;
         writeln!(f, "{INDENT3}host_total_credit: {:.6}", project.host_total_credit)?;
@@ -569,10 +2295,34 @@ impl fmt::Display for Displayable<Project> {
         writeln!(f, "{INDENT3}ended: {}", project.ended)?;
         writeln!(f, "{INDENT3}suspended via GUI: {}", project.suspended_via_gui)?;
         writeln!(f, "{INDENT3}don't request more work: {}", project.dont_request_more_work)?;
-        writeln!(f, "{INDENT3}disk usage: {}", Usage(project.disk_usage))?;
-        writeln!(f, "{INDENT3}last RPC: {}", FormattedTimestamp::new(project.last_rpc_time))?;
+        writeln!(f, "{INDENT3}disk usage: {}", Bytes(project.disk_usage))?;
+        writeln!(f, "{INDENT3}last RPC: {}", project.last_rpc_time.display_in(self.1))?;
         writeln!(f)?;
-        writeln!(f, "{INDENT3}project files downloaded: {}", FormattedTimestamp::new(project.project_files_downloaded_time))?;
+        writeln!(f, "{INDENT3}project files downloaded: {}", project.project_files_downloaded_time.display_in(self.1))?;
+
+        if f.alternate() {
+            writeln!(f, "{INDENT3}venue: {}", Some(project.venue.as_str()).filter(|v| !v.is_empty()).unwrap_or("(default)"))?;
+            writeln!(f, "{INDENT3}host ID: {}", project.hostid)?;
+            writeln!(f, "{INDENT3}project dir: {}", project.project_dir)?;
+            writeln!(f, "{INDENT3}scheduling priority: {:.6}", project.sched_priority)?;
+            writeln!(f, "{INDENT3}download backoff: {}", FormattedDuration(project.download_backoff.0))?;
+            writeln!(f, "{INDENT3}upload backoff: {}", FormattedDuration(project.upload_backoff.0))?;
+            writeln!(f, "{INDENT3}next RPC allowed in: {}",
+                FormattedDuration(project.min_rpc_time.0 - Utc::now().timestamp() as f64))?;
+            let app_config = &project.app_config;
+            if app_config.project_max_concurrent > 0 {
+                writeln!(f, "{INDENT3}max concurrent tasks: {}", app_config.project_max_concurrent)?;
+            }
+            for app in app_config.app.iter().filter(|app| app.max_concurrent > 0) {
+                writeln!(f, "{INDENT3}max concurrent tasks of {}: {}", app.name, app.max_concurrent)?;
+            }
+            for app in &app_config.app {
+                if let Some(gpu_versions) = &app.gpu_versions {
+                    writeln!(f, "{INDENT3}resources per task of {}: {} CPUs, {} GPUs",
+                        app.name, gpu_versions.cpu_usage, gpu_versions.gpu_usage)?;
+                }
+            }
+        }
 
         for gui_url in &project.gui_urls.0 {
             writeln!(f, "GUI URL:")?;
@@ -590,23 +2340,41 @@ impl fmt::Display for Displayable<Project> {
     }
 }
 
+impl fmt::Display for Displayable<StateChange> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0 {
+            StateChange::ProjectAttached(name) => write!(f, "project attached: {}", name),
+            StateChange::ProjectDetached(name) => write!(f, "project detached: {}", name),
+            StateChange::ProjectGainedWork(name) => write!(f, "project gained work: {}", name),
+            StateChange::ProjectRanOutOfWork(name) => write!(f, "project ran out of work: {}", name),
+            StateChange::ProjectSuspended(name) => write!(f, "project suspended: {}", name),
+            StateChange::ProjectResumed(name) => write!(f, "project resumed: {}", name),
+            StateChange::TaskAdded(name) => write!(f, "task added: {}", name),
+            StateChange::TaskRemoved(name) => write!(f, "task removed: {}", name),
+            StateChange::TaskStarted(name) => write!(f, "task started: {}", name),
+            StateChange::TaskFinished(name, state) => write!(f, "task finished: {} ({})", name, state),
+            StateChange::TaskStateChanged(name, from, to) => write!(f, "task {}: {} -> {}", name, from, to),
+        }
+    }
+}
+
 impl fmt::Display for Displayable<Task> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let task = &self.0;
         let scheduler_state = task
             .active_task
             .as_ref()
-            .map_or(SchedulerState::Uninitialized, |t| t.scheduler_state.clone());
+            .map_or(SchedulerState::Uninitialized, |t| t.scheduler_state);
         let active_task_state = task
             .active_task
             .as_ref()
-            .map_or(ActiveTaskState::Uninitialized, |at| at.active_task_state.clone());
+            .map_or(ActiveTaskState::Uninitialized, |at| at.active_task_state);
 
         writeln!(f, "{INDENT3}name: {}", task.name)?;
         writeln!(f, "{INDENT3}WU name: {}", task.wu_name)?;
         writeln!(f, "{INDENT3}project URL: {}", task.project_url)?;
-        writeln!(f, "{INDENT3}received: {}", FormattedTimestamp::new(task.received_time))?;
-        writeln!(f, "{INDENT3}report deadline: {}", FormattedTimestamp::new(task.report_deadline))?;
+        writeln!(f, "{INDENT3}received: {}", task.received_time.display_in(self.1))?;
+        writeln!(f, "{INDENT3}report deadline: {}", task.report_deadline.display_in(self.1))?;
         writeln!(f, "{INDENT3}ready to report: {}", task.ready_to_report)?;
         writeln!(f, "{INDENT3}state: {}", task.state)?;
         writeln!(f, "{INDENT3}scheduler state: {}", scheduler_state)?;
@@ -615,9 +2383,7 @@ impl fmt::Display for Displayable<Task> {
         writeln!(f, "{INDENT3}resources: {}",
             Some(task.resources.as_str()).filter(|rs| !rs.is_empty()).get_or_insert("1 CPU"))?;
 
-        if task.state as isize >= 0
-            && task.state as isize <= ResultClientState::FilesDownloaded as isize
-        {
+        if task.state >= ResultClientState::New && task.state <= ResultClientState::FilesDownloaded {
             if task.suspended_via_gui.into() {
                 writeln!(f, "{INDENT3}suspended via GUI: yes")?;
             }
@@ -625,17 +2391,28 @@ impl fmt::Display for Displayable<Task> {
             if let Some(active_task) = &task.active_task {
                 writeln!(f, "{INDENT3}elapsed task time: {:.6}", active_task.elapsed_time.0)?;
             }
+            if let Some(eta) = task.estimated_completion(Timestamp(Utc::now().timestamp() as f64)) {
+                writeln!(f, "{INDENT3}ETA: {}", eta.display_in(self.1))?;
+            }
+            if let Some(health) = task.progress_health() {
+                writeln!(f, "{INDENT3}progress: {}", health)?;
+            }
         }
 
-        if scheduler_state as isize > SchedulerState::Uninitialized as isize {
+        if scheduler_state > SchedulerState::Uninitialized {
             if let Some(active_task) = &task.active_task {
                 writeln!(f, "{INDENT3}slot: {}", active_task.slot)?;
                 writeln!(f, "{INDENT3}PID: {}", active_task.pid)?;
                 writeln!(f, "{INDENT3}CPU time at last checkpoint: {:.6}", active_task.checkpoint_cpu_time.0)?;
                 writeln!(f, "{INDENT3}current CPU time: {:.6}", active_task.current_cpu_time.0)?;
                 writeln!(f, "{INDENT3}fraction done: {:.6}", active_task.fraction_done)?;
-                writeln!(f, "{INDENT3}swap size: {:.0} MB", to_mibi(active_task.swap_size))?;
-                writeln!(f, "{INDENT3}working set size: {:.0} MB", to_mibi(active_task.working_set_size_smoothed))?;
+                if f.alternate() {
+                    writeln!(f, "{INDENT3}swap size: {:#}", Bytes(active_task.swap_size))?;
+                    writeln!(f, "{INDENT3}working set size: {:#}", Bytes(active_task.working_set_size_smoothed))?;
+                } else {
+                    writeln!(f, "{INDENT3}swap size: {}", Bytes(active_task.swap_size))?;
+                    writeln!(f, "{INDENT3}working set size: {}", Bytes(active_task.working_set_size_smoothed))?;
+                }
 
                 if active_task.bytes_sent > 0. || active_task.bytes_received > 0. {
                     writeln!(f, "{INDENT3}bytes sent: {:.0} received: {:.0}",
@@ -644,7 +2421,13 @@ impl fmt::Display for Displayable<Task> {
             }
         }
 
-        if task.state as isize > ResultClientState::FilesDownloaded as isize {
+        if let Some(active_task) = &task.active_task {
+            for warning in memory_warnings(active_task) {
+                writeln!(f, "{INDENT3}warning: {}", warning)?;
+            }
+        }
+
+        if task.state > ResultClientState::FilesDownloaded {
             writeln!(f, "{INDENT3}final CPU time: {:.6}", task.final_cpu_time.0)?;
             writeln!(f, "{INDENT3}final elapsed time: {:.6}", task.final_elapsed_time.0)?;
             writeln!(f, "{INDENT3}exit_status: {}", task.exit_status)?;
@@ -660,3 +2443,985 @@ impl fmt::Display for Displayable<Version> {
         self.0.fmt(f)
     }
 }
+
+// ----- Tests -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libjoinc::rpc::testing::{FakeClock, ScriptedTransport};
+
+    // Replies to the requests by their tag, the get_results replies in turn, and logs them.
+    fn scripted_client(results: &[&str], client_version: Version) -> ScriptedTransport {
+        let server_version = format!(
+            "<server_version><major>{}</major><minor>{}</minor><release>{}</release></server_version>",
+            client_version.major, client_version.minor, client_version.release);
+        results.iter().fold(ScriptedTransport::new(), |client, result| client.reply_to("get_results", result))
+            .reply_to("get_cc_status", "<cc_status><task_mode_perm>2</task_mode_perm></cc_status>")
+            .reply_to("exchange_versions", &server_version)
+            .reply_to("get_cc_config", "<cc_config><log_flags><task>1</task></log_flags><options><use_all_gpus>1</use_all_gpus></options></cc_config>")
+            .reply("<success/>")
+    }
+
+    fn run_until_idle_options(max_duration: u64) -> PollOptions {
+        PollOptions {
+            overall_timeout: Some(std::time::Duration::from_secs(max_duration)),
+            interval: std::time::Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn keeps_unmodeled_options_when_setting_log_flag() {
+        let mut client = scripted_client(&[], Version::new(7, 24, 1));
+        set_log_flag(&mut client, "cpu_sched", true).unwrap();
+
+        assert_eq!(client.requests().len(), 4);
+        assert_eq!(client.requests()[2], concat!(
+            "<set_cc_config><cc_config><log_flags><task>1</task><cpu_sched>1</cpu_sched></log_flags>",
+            "<options><use_all_gpus>1</use_all_gpus></options></cc_config></set_cc_config>"));
+        assert!(client.requests()[3].starts_with("<read_cc_config"));
+    }
+
+    #[test]
+    fn refuses_setting_log_flag_of_old_client() {
+        let mut client = scripted_client(&[], Version::new(7, 0, 64));
+        assert!(matches!(set_log_flag(&mut client, "cpu_sched", true),
+            Err(Error::Client(msg)) if msg == "requires client >= 7.2.0"));
+        assert_eq!(client.requests().len(), 1);
+    }
+
+    #[test]
+    fn warns_about_memory_needs_of_tasks() {
+        let active_task = |too_large: bool, needs_shmem: bool| ActiveTask {
+            too_large: too_large.into(),
+            needs_shmem: needs_shmem.into(),
+            ..Default::default()
+        };
+        assert!(memory_warnings(&active_task(false, false)).is_empty());
+        assert_eq!(memory_warnings(&active_task(true, true)),
+            ["task exceeds memory bound", "task is waiting for shared memory"]);
+
+        let task = Task {
+            active_task: Some(ActiveTask { working_set_size_smoothed: 8e9, ..active_task(true, false) }),
+            ..Default::default()
+        };
+        let displayed = format!("{}", task.display());
+        assert!(displayed.contains("   warning: task exceeds memory bound\n"));
+        assert!(!displayed.contains("shared memory"));
+    }
+
+    #[test]
+    fn sorts_and_filters_disk_usage() {
+        let projects = || vec![
+            DiskUsageSummaryProject { master_url: "https://b.org/".to_string(), disk_usage: 10., ..Default::default() },
+            DiskUsageSummaryProject { master_url: "https://c.org/".to_string(), disk_usage: 30., ..Default::default() },
+            DiskUsageSummaryProject { master_url: "https://a.org/".to_string(), disk_usage: 20., ..Default::default() },
+        ];
+        let arranged = |sort, min_bytes| {
+            let mut projects = projects();
+            arrange_disk_usage(&mut projects, sort, min_bytes);
+            projects.into_iter().map(|project| project.master_url).collect::<Vec<_>>()
+        };
+
+        assert_eq!(arranged(None, None), ["https://b.org/", "https://c.org/", "https://a.org/"]);
+        assert_eq!(arranged(Some(DiskUsageSort::Usage), None), ["https://c.org/", "https://a.org/", "https://b.org/"]);
+        assert_eq!(arranged(Some(DiskUsageSort::Name), None), ["https://a.org/", "https://b.org/", "https://c.org/"]);
+        assert_eq!(arranged(None, Some(20.)), ["https://c.org/", "https://a.org/"]);
+    }
+
+    #[test]
+    fn keeps_disk_usage_totals_of_hidden_projects() {
+        let mut summary = DiskUsageSummary { total: 1000., free: 400., ..Default::default() };
+        summary.projects.push(DiskUsageSummaryProject { disk_usage: 10., ..Default::default() });
+        arrange_disk_usage(&mut summary.projects, None, Some(1024.));
+
+        assert!(summary.projects.is_empty());
+        assert!(summary.display().to_string().starts_with("total: 1000 B\nfree: 400 B\n"));
+    }
+
+    #[test]
+    fn derives_on_error_from_the_later_flag() {
+        let on_error = |args: &[&str]| on_error(&Cli::try_parse_from(args).ok().unwrap());
+
+        assert_eq!(on_error(&["joinccmd", "check"]), OnError::Continue);
+        assert_eq!(on_error(&["joinccmd", "--fail-fast", "check"]), OnError::Stop);
+        assert_eq!(on_error(&["joinccmd", "--keep-going", "check"]), OnError::Continue);
+        assert_eq!(on_error(&["joinccmd", "--fail-fast", "--keep-going", "check"]), OnError::Continue);
+        assert_eq!(on_error(&["joinccmd", "--keep-going", "--fail-fast", "check"]), OnError::Stop);
+    }
+
+    #[test]
+    fn runs_each_target_until_failure_for_fail_fast() {
+        let op = |ran: &mut Vec<i32>, target: &i32| {
+            ran.push(*target);
+            match target % 2 {
+                0 => Err(Error::Rejected(format!("target {}", target), -1)),
+                _ => Ok(()),
+            }
+        };
+
+        let mut ran = vec![];
+        let results = run_each(&[1, 2, 3, 4, 5], OnError::Stop, |target| op(&mut ran, target));
+        assert_eq!(ran, [1, 2]);
+        assert_eq!(results.iter().map(Result::is_ok).collect::<Vec<_>>(), [true, false]);
+
+        let mut ran = vec![];
+        let results = run_each(&[1, 2, 3, 4, 5], OnError::Continue, |target| op(&mut ran, target));
+        assert_eq!(ran, [1, 2, 3, 4, 5]);
+        assert_eq!(results.iter().map(Result::is_ok).collect::<Vec<_>>(), [true, false, true, false, true]);
+        assert!(matches!(first_failure(results), Err(Error::Rejected(msg, _)) if msg == "target 2"));
+    }
+
+    #[test]
+    fn succeeds_if_no_target_failed() {
+        assert!(first_failure(run_each(&[1, 3], OnError::Continue, |_| Ok(()))).is_ok());
+        assert!(first_failure(run_each::<i32>(&[], OnError::Stop, |_| Ok(()))).is_ok());
+    }
+
+    #[test]
+    fn sums_network_traffic_of_active_tasks() {
+        let task = |traffic: Option<(f64, f64)>| Task {
+            active_task: traffic.map(|(bytes_sent, bytes_received)| ActiveTask { bytes_sent, bytes_received, ..Default::default() }),
+            ..Default::default()
+        };
+
+        assert_eq!(network_totals(&[]), (0., 0.));
+        assert_eq!(network_totals(&[task(Some((1024., 2048.))), task(None), task(Some((512., 0.)))]), (1536., 2048.));
+    }
+
+    #[test]
+    fn finds_tasks_at_risk_nearest_deadline_first() {
+        let task = |name: &str, report_deadline: f64, ready_to_report: bool| Task {
+            name: name.to_string(),
+            report_deadline: Timestamp(report_deadline),
+            ready_to_report: ready_to_report.into(),
+            ..Default::default()
+        };
+        let now = Timestamp(100_000.);
+        let tasks = vec![
+            task("later", 100_000. + 7200., false),
+            task("beyond", 100_000. + 7201., false),
+            task("reported", 100_000. + 60., true),
+            task("soon", 100_000. + 60., false),
+            task("overdue", 99_000., false),
+            task("unset", 0., false),
+        ];
+
+        let names: Vec<String> = tasks_at_risk(tasks, now, 7200.).into_iter().map(|task| task.name).collect();
+        assert_eq!(names, ["overdue", "soon", "later"]);
+    }
+
+    #[test]
+    fn detects_idle_client() {
+        let task = |state| Task { state, ..Default::default() };
+
+        assert!(is_idle(&[]));
+        assert!(is_idle(&[task(ResultClientState::FilesUploading), task(ResultClientState::FilesUploaded)]));
+        assert!(!is_idle(&[task(ResultClientState::FilesUploaded), task(ResultClientState::FilesDownloaded)]));
+        assert!(!is_idle(&[task(ResultClientState::New)]));
+    }
+
+    #[test]
+    fn runs_until_idle_and_restores_run_mode() {
+        let busy = "<results><result><state>2</state></result></results>";
+        let mut client = scripted_client(&[busy, busy, "<results/>"], Version::new(7, 24, 1));
+        let clock = FakeClock::default();
+
+        let (restored, idle) = run_until_idle(&mut client, &clock, &run_until_idle_options(3600)).unwrap();
+
+        assert_eq!(restored, RunMode::Auto);
+        assert!(idle);
+        assert_eq!(client.requests().iter().filter(|r| r.starts_with("<get_results")).count(), 3);
+        assert!(client.requests()[1].starts_with("<set_run_mode><always/>"));
+        assert!(client.requests().last().unwrap().starts_with("<set_run_mode><auto/>"));
+    }
+
+    #[test]
+    fn restores_run_mode_at_max_duration() {
+        let busy = "<results><result><state>2</state></result></results>";
+        let mut client = scripted_client(&[busy], Version::new(7, 24, 1));
+        let clock = FakeClock::default();
+
+        let (restored, idle) = run_until_idle(&mut client, &clock, &run_until_idle_options(120)).unwrap();
+
+        assert_eq!(restored, RunMode::Auto);
+        assert!(!idle);
+        assert!(client.requests().last().unwrap().starts_with("<set_run_mode><auto/>"));
+    }
+
+    #[test]
+    fn toggles_only_tasks_not_in_target_state() {
+        let running = Task { suspended_via_gui: Bool::False, ..Default::default() };
+        let suspended = Task { suspended_via_gui: Bool::True, ..Default::default() };
+
+        assert!(needs_toggle(&running, true));
+        assert!(!needs_toggle(&suspended, true));
+        assert!(!needs_toggle(&running, false));
+        assert!(needs_toggle(&suspended, false));
+    }
+
+    #[test]
+    fn maps_cc_status_to_check_exit_code() {
+        let computing = CCStatus { task_suspend_reason: SuspendReason::NotSuspended, ..Default::default() };
+        let suspended = CCStatus { task_suspend_reason: SuspendReason::UserReq, ..Default::default() };
+
+        assert_eq!(check_exit_code(&computing), 0);
+        assert_eq!(check_exit_code(&suspended), 2);
+    }
+
+    #[test]
+    fn selects_file_transfers_to_retry() {
+        let stalled = FileTransfer {
+            name: "stalled".to_string(),
+            project_url: "http://p.org/".to_string(),
+            project_backoff: Duration(60.),
+            ..Default::default()
+        };
+        let fine = FileTransfer { name: "fine".to_string(), ..Default::default() };
+        let file_transfers = vec![stalled, fine];
+
+        assert_eq!(select_file_transfers(&file_transfers, false).len(), 2);
+        assert_eq!(
+            select_file_transfers(&file_transfers, true),
+            vec![("http://p.org/".to_string(), "stalled".to_string())]
+        );
+    }
+
+    #[test]
+    fn maps_cli_values_to_library_types() {
+        for op in SupportedFileTransferOp::value_variants() {
+            let _: FileTransferOp = op.clone().into();
+        }
+        for op in SupportedProjectOp::value_variants() {
+            let _: ProjectOp = op.clone().into();
+        }
+        for mode in SupportedRunMode::value_variants() {
+            let _: SettableRunMode = mode.clone().into();
+        }
+        for op in SupportedTaskOp::value_variants() {
+            let _: TaskOp = op.clone().into();
+        }
+        assert_eq!(ProjectOp::from(SupportedProjectOp::DontDetachWhenDone), ProjectOp::DontDetachWhenDone);
+    }
+
+    #[test]
+    fn parses_mode_durations() {
+        assert_eq!(parse_mode_duration("0"), Ok(0.));
+        assert_eq!(parse_mode_duration("3600"), Ok(3600.));
+        assert!(parse_mode_duration("-1").is_err());
+        assert!(parse_mode_duration("NaN").is_err());
+        assert!(parse_mode_duration("inf").is_err());
+        assert!(parse_mode_duration("an hour").is_err());
+    }
+
+    #[test]
+    fn filters_projects() {
+        let idle = Project::default();
+        let suspended = Project { suspended_via_gui: Bool::True, ..Default::default() };
+        let failing = Project { nrpc_failures: 2, dont_request_more_work: Bool::True, ..Default::default() };
+        let no_filter = ProjectFilter { no_more_work: false, suspended: false, errors: false };
+
+        assert!(no_filter.matches(&idle));
+        assert!(requests_no_more_work(&failing) && !requests_no_more_work(&idle));
+        assert!(is_suspended(&suspended) && !is_suspended(&failing));
+        assert!(has_errors(&failing) && !has_errors(&suspended));
+        assert!(has_errors(&Project { master_fetch_failures: 1, ..Default::default() }));
+
+        let errors_without_work = ProjectFilter { no_more_work: true, suspended: false, errors: true };
+        assert!(errors_without_work.matches(&failing));
+        assert!(!errors_without_work.matches(&Project { nrpc_failures: 1, ..Default::default() }));
+        assert!(!errors_without_work.matches(&idle));
+    }
+
+    #[test]
+    fn detects_gpu_usage() {
+        assert!(!uses_gpu(""));
+        assert!(!uses_gpu("1 CPU"));
+        assert!(!uses_gpu("4 CPUs"));
+        assert!(uses_gpu("0.5 CPUs + 1 NVIDIA GPU"));
+        assert!(uses_gpu("0.985 CPUs + 1 NVIDIA GPU (device 1)"));
+        assert!(uses_gpu("0.01 CPUs + 1 AMD/ATI GPU"));
+        assert!(uses_gpu("1 CPU + 0.5 intel_gpu GPUs"));
+        assert!(uses_gpu("0.2 CPUs + 1 Apple GPU"));
+    }
+
+    #[test]
+    fn computes_credit_rates() {
+        let project = |name: &str, url: &str, host_total_credit| Project {
+            project_name: name.to_string(),
+            master_url: url.to_string(),
+            host_total_credit,
+            ..Default::default()
+        };
+        let before = vec![project("A", "http://a.org/", 100.), project("B", "http://b.org/", 50.)];
+        let after = vec![project("A", "http://a.org", 130.), project("C", "http://c.org/", 10.)];
+
+        assert_eq!(credit_rates(&before, &after, 1800.), vec![
+            CreditRate { project_name: "A".to_string(), change: CreditChange::PerHour(60.) },
+            CreditRate { project_name: "C".to_string(), change: CreditChange::Attached },
+            CreditRate { project_name: "B".to_string(), change: CreditChange::Detached },
+        ]);
+        assert_eq!(credit_rates(&before, &[], 60.).len(), 2);
+    }
+
+    #[test]
+    fn renders_saved_state() {
+        let reply = "\n<client_state><project><master_url>https://a.org/</master_url></project></client_state>\n";
+        let saved = render_saved_state(reply, "2024-02-01T12:00:00+00:00", Version::new(7, 24, 1)).unwrap();
+
+        assert!(saved.starts_with(&format!(
+            "<!-- saved by joinccmd {} at 2024-02-01T12:00:00+00:00 from client 7.24.1 -->\n<client_state>\n  <project>\n",
+            JOINCCMD_VERSION)));
+        let state = GetStateCommand::parse(&saved).unwrap();
+        assert_eq!(state.projects[0].master_url, "https://a.org/");
+    }
+
+    #[test]
+    fn diffs_states() {
+        let project = |name: &str, suspended_via_gui| Project {
+            project_name: name.to_string(),
+            master_url: format!("https://{}.org/", name),
+            suspended_via_gui,
+            ..Default::default()
+        };
+        let task = |name: &str, project: &str, state, started: bool| Task {
+            name: name.to_string(),
+            project_url: format!("https://{}.org/", project),
+            state,
+            active_task: Some(ActiveTask::default()).filter(|_| started),
+            ..Default::default()
+        };
+
+        let a = CCState {
+            projects: vec![project("a", Bool::False), project("b", Bool::False), project("gone", Bool::False)],
+            tasks: vec![
+                task("computed", "a", ResultClientState::FilesDownloaded, true),
+                task("waiting", "a", ResultClientState::FilesDownloaded, false),
+                task("uploading", "a", ResultClientState::FilesUploading, false),
+                task("reported", "a", ResultClientState::FilesUploaded, false),
+            ],
+            ..Default::default()
+        };
+        let b = CCState {
+            projects: vec![project("new", Bool::False), project("a", Bool::True), project("b", Bool::False)],
+            tasks: vec![
+                task("computed", "a", ResultClientState::FilesUploading, true),
+                task("waiting", "a", ResultClientState::FilesDownloaded, true),
+                task("uploading", "a", ResultClientState::FilesUploaded, false),
+                task("fresh", "b", ResultClientState::FilesDownloading, false),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(diff_states(&a, &b), vec![
+            StateChange::ProjectAttached("new".to_string()),
+            StateChange::ProjectSuspended("a".to_string()),
+            StateChange::ProjectGainedWork("b".to_string()),
+            StateChange::ProjectDetached("gone".to_string()),
+            StateChange::TaskFinished("computed".to_string(), ResultClientState::FilesUploading),
+            StateChange::TaskStarted("waiting".to_string()),
+            StateChange::TaskStateChanged("uploading".to_string(),
+                ResultClientState::FilesUploading, ResultClientState::FilesUploaded),
+            StateChange::TaskAdded("fresh".to_string()),
+            StateChange::TaskRemoved("reported".to_string()),
+        ]);
+        assert_eq!(diff_states(&b, &b), vec![]);
+        assert_eq!(StateChange::TaskStateChanged("t".to_string(),
+            ResultClientState::FilesUploading, ResultClientState::FilesUploaded).display().to_string(),
+            "task t: uploading -> uploaded");
+    }
+
+    #[test]
+    fn summarizes_projects() {
+        let projects = vec![
+            Project { user_total_credit: 100., host_total_credit: 10., resource_share: 50., ..Default::default() },
+            Project { user_total_credit: 20.5, host_total_credit: 2.5, resource_share: 100., ..Default::default() },
+        ];
+
+        let expected = ProjectSummary { user_total_credit: 120.5, host_total_credit: 12.5, resource_share: 150. };
+
+        assert_eq!(summarize(&projects), expected);
+        assert_eq!(summarize(&[]), ProjectSummary::default());
+    }
+
+    #[test]
+    fn computes_resource_share_percentages() {
+        let projects = vec![
+            Project { resource_share: 500., ..Default::default() },
+            Project { resource_share: 300., ..Default::default() },
+            Project { resource_share: 0., ..Default::default() },
+        ];
+
+        assert_eq!(resource_share_percentages(&projects), vec![Some(62.5), Some(37.5), Some(0.)]);
+        assert_eq!(resource_share_percentages(&[Project::default(), Project::default()]), vec![None, None]);
+    }
+
+    #[test]
+    fn ranks_projects_by_priority() {
+        let project = |url: &str, sched_priority: f64| {
+            (Project { master_url: url.to_string(), sched_priority, ..Default::default() }, ())
+        };
+        let urls = |projects: &[(Project, ())]| projects.iter().map(|(p, _)| p.master_url.clone()).collect::<Vec<_>>();
+
+        let mut projects = vec![project("https://c/", -1.), project("https://b/", 0.5), project("https://a/", 0.5)];
+        assert_eq!(rank_by_priority(&mut projects), Some(0));
+        assert_eq!(urls(&projects), vec!["https://a/", "https://b/", "https://c/"]);
+
+        projects[0].0.suspended_via_gui = Bool::True;
+        projects[1].0.dont_request_more_work = Bool::True;
+        assert_eq!(rank_by_priority(&mut projects), Some(2));
+
+        projects[2].0.ended = Bool::True;
+        assert_eq!(rank_by_priority(&mut projects), None);
+        assert_eq!(rank_by_priority::<()>(&mut []), None);
+    }
+
+    #[test]
+    fn displays_scheduling_details_if_verbose() {
+        let project = Project {
+            sched_priority: -0.5,
+            download_backoff: Duration(90.),
+            upload_backoff: Duration(3723.),
+            min_rpc_time: Timestamp(Utc::now().timestamp() as f64 + 600.),
+            ..Default::default()
+        };
+        let displayed = format!("{:#}", (project, None).display());
+
+        assert!(displayed.contains("scheduling priority: -0.500000\n"));
+        assert!(displayed.contains("download backoff: 1m 30s\n"));
+        assert!(displayed.contains("upload backoff: 1h 02m 03s\n"));
+        assert!(displayed.contains("next RPC allowed in: 10m 00s\n") || displayed.contains("next RPC allowed in: 9m 59s\n"));
+        assert!(!format!("{}", (Project::default(), None).display()).contains("backoff"));
+    }
+
+    #[test]
+    fn formats_bytes() {
+        assert_eq!(format_bytes(0.), "0 B");
+        assert_eq!(format_bytes(1023.), "1023 B");
+        assert_eq!(format_bytes(1024.), "1.0 KiB");
+        assert_eq!(format_bytes(4096.), "4.0 KiB");
+        assert_eq!(format_bytes(99.94 * 1024.), "99.9 KiB");
+        assert_eq!(format_bytes(100. * 1024.), "100 KiB");
+        assert_eq!(format_bytes(1023. * 1024.), "1023 KiB");
+        assert_eq!(format_bytes(1024. * 1024.), "1.0 MiB");
+        assert_eq!(format_bytes(512. * 1024. * 1024.), "512 MiB");
+        assert_eq!(format_bytes(1.5 * 1024. * 1024. * 1024.), "1.5 GiB");
+        assert_eq!(format_bytes(2. * 1024f64.powi(4)), "2.0 TiB");
+        assert_eq!(format_bytes(3. * 1024f64.powi(6)), "3072 PiB");
+    }
+
+    #[test]
+    fn formats_memory_of_coprocs_as_bytes() {
+        let coprocs = Coprocs {
+            amds: vec![CoprocAMD {
+                count: 1,
+                local_ram: 2048,
+                available_ram: 1.5 * 1024. * 1024. * 1024.,
+                peak_flops: 4e12,
+                name: "Tahiti".to_string(),
+                version: "1.4.1848".to_string(),
+                opencl: Some(CoprocOpenCL {
+                    name: "Tahiti".to_string(),
+                    global_mem_size: 3. * 1024. * 1024. * 1024.,
+                    ..Default::default()
+                }),
+            }],
+            ..Default::default()
+        };
+        let displayed = format!("{}", (&coprocs).display());
+        assert!(displayed.contains("AMD GPU: Tahiti (CAL version 1.4.1848, 2.0 GiB, 1.5 GiB available, 4000 GFLOPS peak)\n"));
+        assert!(displayed.contains(", 3.0 GiB, 1.5 GiB available, 4000 GFLOPS peak)\n"));
+    }
+
+    #[test]
+    fn displays_exact_sizes_if_verbose() {
+        let host_info = || HostInfo { m_nbytes: 1.5 * 1024. * 1024. * 1024., ..Default::default() };
+
+        assert!(format!("{}", host_info().display()).contains("mem size: 1.5 GiB\n"));
+        assert!(format!("{:#}", host_info().display()).contains("mem size: 1610612736.000000\n"));
+    }
+
+    #[test]
+    fn displays_set_manager_flags_as_warnings() {
+        let displayed = CCStatus { manager_must_quit: true.into(), ..Default::default() }.display().to_string();
+        assert!(displayed.ends_with("perm becomes current in 0 sec\nwarning: the manager must quit\n"));
+        assert!(!CCStatus::default().display().to_string().contains("warning"));
+    }
+
+    #[test]
+    fn displays_timestamps_in_the_time_format() {
+        let displayed = |format: TimeFormat| {
+            let msg = Message { seqno: 7, timestamp: Timestamp(1706933106.), ..Default::default() };
+            msg.display_in(format.into()).to_string()
+        };
+
+        let local = DateTime::from_timestamp(1706933106, 0).unwrap().with_timezone(&Local);
+        assert!(displayed(TimeFormat::Local).starts_with(&format!("7: {} ", local.format("%d-%b-%Y %H:%M:%S"))));
+        assert!(displayed(TimeFormat::Iso8601).starts_with("7: 2024-02-03T04:05:06Z "));
+        assert!(displayed(TimeFormat::Epoch).starts_with("7: 1706933106 "));
+
+        let task = Task { report_deadline: Timestamp(1706933106.), ..Default::default() };
+        assert!(task.display_in(TimeStyle::Iso8601).to_string().contains("report deadline: 2024-02-03T04:05:06Z\n"));
+    }
+
+    #[test]
+    fn displays_venue_if_verbose() {
+        let at_home = Project { venue: "home".to_string(), hostid: 42, ..Default::default() };
+
+        let displayed = format!("{:#}", (at_home, None).display());
+        assert!(displayed.contains("venue: home\n"));
+        assert!(displayed.contains("host ID: 42\n"));
+        assert!(format!("{:#}", (Project::default(), None).display()).contains("venue: (default)\n"));
+    }
+
+    #[test]
+    fn displays_app_config_if_verbose() {
+        let limited = Project {
+            app_config: ProjectAppConfig {
+                project_max_concurrent: 2,
+                app: vec![AppConfig {
+                    name: "sim".to_string(),
+                    max_concurrent: 1,
+                    gpu_versions: Some(GpuVersions { cpu_usage: 0.5, gpu_usage: 0.25 }),
+                }],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let displayed = format!("{:#}", (limited, None).display());
+        assert!(displayed.contains("max concurrent tasks: 2\n"));
+        assert!(displayed.contains("max concurrent tasks of sim: 1\n"));
+        assert!(displayed.contains("resources per task of sim: 0.5 CPUs, 0.25 GPUs\n"));
+    }
+
+    #[test]
+    fn parses_state_sections() {
+        let sections = parse_state_sections("host,projects").unwrap();
+
+        assert!(sections.contains(StateSections::HOST));
+        assert!(sections.contains(StateSections::PROJECTS));
+        assert!(!sections.contains(StateSections::TASKS));
+        assert_eq!(parse_state_sections("apps, time-stats,net-stats,tasks,host,projects"), Ok(StateSections::ALL));
+        assert!(parse_state_sections("projects,hosts").is_err());
+        assert!(parse_state_sections("").is_err());
+    }
+
+    #[test]
+    fn counts_tasks_by_state() {
+        let running = |fraction_done| Task {
+            state: ResultClientState::FilesDownloaded,
+            active_task: Some(ActiveTask {
+                scheduler_state: SchedulerState::Scheduled,
+                fraction_done,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let preempted = Task {
+            state: ResultClientState::FilesDownloaded,
+            active_task: Some(ActiveTask { scheduler_state: SchedulerState::Preempted, ..Default::default() }),
+            ..Default::default()
+        };
+        let ready = Task { state: ResultClientState::FilesDownloaded, ..Default::default() };
+        let uploading = Task { state: ResultClientState::FilesUploading, ..Default::default() };
+        let tasks = vec![running(0.25), running(0.5), preempted, ready, uploading];
+
+        let expected = TaskCounts { running: 2, ready: 2, total: 5, fraction_done: 0.375 };
+
+        assert_eq!(count_tasks(&tasks), expected);
+        assert_eq!(count_tasks(&[]), TaskCounts::default());
+    }
+
+    #[test]
+    fn maps_errors_to_exit_codes() {
+        let io_error = io::Error::new(io::ErrorKind::ConnectionRefused, "refused");
+
+        assert_eq!(exit_code(&Error::Io(io_error)), 1);
+        assert_eq!(exit_code(&Error::Rpc("no reply".to_string())), 1);
+        assert_eq!(exit_code(&Error::Unauthorized), 2);
+        assert_eq!(exit_code(&Error::Client("Missing URL".to_string())), 3);
+        assert_eq!(exit_code(&Error::Rejected("Benchmarks running".to_string(), -108)), 3);
+        assert_eq!(exit_code(&Error::InvalidArgument("negative duration".to_string())), 5);
+    }
+
+    #[test]
+    fn exits_with_invalid_argument_on_parse_errors() {
+        let exit_code = |args: &[&str]| parse_error_exit_code(&Cli::try_parse_from(args).err().unwrap());
+
+        assert_eq!(exit_code(&["joinccmd", "check", "--bogus"]), 5);
+        assert_eq!(exit_code(&["joinccmd", "--port", "abc", "check"]), 5);
+        assert_eq!(exit_code(&["joinccmd", "set-run-mode", "never", "--", "-1"]), 5);
+        assert_eq!(exit_code(&["joinccmd", "--help"]), 0);
+    }
+
+    #[test]
+    fn resolves_target_by_precedence() {
+        let config = Config {
+            default: Profile { host: Some("default.lan".to_string()), port: None, passwd: Some("secret".to_string()) },
+            host: [(
+                "cruncher".to_string(),
+                Profile { host: Some("cruncher.lan".to_string()), port: Some(1234), passwd: None },
+            )]
+            .into_iter()
+            .collect(),
+        };
+
+        assert_eq!(
+            resolve_target(Profile::default(), &Config::default(), None),
+            Ok(Target { host: "localhost".to_string(), port: connection::DEFAULT_PORT, passwd: None })
+        );
+        assert_eq!(
+            resolve_target(Profile::default(), &config, None),
+            Ok(Target { host: "default.lan".to_string(), port: connection::DEFAULT_PORT, passwd: Some("secret".to_string()) })
+        );
+        assert_eq!(
+            resolve_target(Profile::default(), &config, Some("cruncher")),
+            Ok(Target { host: "cruncher.lan".to_string(), port: 1234, passwd: Some("secret".to_string()) })
+        );
+
+        let cli = Profile { host: None, port: Some(4321), passwd: Some("other".to_string()) };
+        assert_eq!(
+            resolve_target(cli, &config, Some("cruncher")),
+            Ok(Target { host: "cruncher.lan".to_string(), port: 4321, passwd: Some("other".to_string()) })
+        );
+        assert!(resolve_target(Profile::default(), &config, Some("unknown")).is_err());
+    }
+
+    #[test]
+    fn skips_auth_for_empty_password() {
+        let config = Config {
+            default: Profile { passwd: Some("secret".to_string()), ..Default::default() },
+            host: [("open".to_string(), Profile { passwd: Some(String::new()), ..Default::default() })]
+                .into_iter()
+                .collect(),
+        };
+
+        let empty = Profile { passwd: Some(String::new()), ..Default::default() };
+        assert_eq!(resolve_target(empty, &config, None).unwrap().passwd, None);
+        assert_eq!(resolve_target(Profile::default(), &config, Some("open")).unwrap().passwd, None);
+
+        let cli = Cli::parse_from(["joinccmd", "--no-auth", "status"]);
+        let no_auth = Profile { passwd: cli_passwd(&cli), ..Default::default() };
+        assert_eq!(resolve_target(no_auth, &config, None).unwrap().passwd, None);
+        assert!(Cli::try_parse_from(["joinccmd", "--no-auth", "--passwd", "secret", "status"]).is_err());
+    }
+
+    #[cfg(feature = "config")]
+    #[test]
+    fn parses_config_file() {
+        let config: Config = toml::from_str(concat!(
+            "[default]\npasswd = \"secret\"\n",
+            "[host.cruncher]\nhost = \"cruncher.lan\"\nport = 1234\n",
+        )).unwrap();
+
+        assert_eq!(config.default.passwd.as_deref(), Some("secret"));
+        assert_eq!(config.host["cruncher"].port, Some(1234));
+        assert!(toml::from_str::<Config>("[default]\npassword = \"typo\"\n").is_err());
+    }
+
+    fn targets(hosts: &[&str]) -> Vec<(String, Target)> {
+        hosts.iter().map(|host| (host.to_string(), Target { host: host.to_string(), port: 31416, passwd: None })).collect()
+    }
+
+    // Connects to the given hosts by their name, refusing the connection to all others.
+    fn connector<'a>(hosts: &'a [(&str, ScriptedTransport)]) -> impl FnMut(&Target) -> Result<ScriptedTransport> + 'a {
+        |target| hosts.iter()
+            .find(|(host, _)| *host == target.host)
+            .map(|(_, client)| client.clone())
+            .ok_or_else(|| Error::Io(std::io::ErrorKind::ConnectionRefused.into()))
+    }
+
+    #[test]
+    fn runs_command_on_all_hosts_despite_failures() {
+        let first = ScriptedTransport::replying(["<server_version><major>7</major><minor>24</minor><release>1</release></server_version>"]);
+        let last = ScriptedTransport::replying(["<server_version><major>8</major><minor>0</minor><release>2</release></server_version>"]);
+        let hosts = [("first", first.clone()), ("last", last.clone())];
+
+        let failed = run_on_all_hosts(&targets(&["first", "down", "last"]), connector(&hosts),
+            &CliCommand::ClientVersion, OnError::Continue, TimeStyle::default());
+
+        assert_eq!(failed, 1);
+        assert_eq!(first.request_tags(), ["exchange_versions"]);
+        assert_eq!(last.request_tags(), ["exchange_versions"]);
+    }
+
+    #[test]
+    fn stops_at_first_failed_host_for_fail_fast() {
+        let first = ScriptedTransport::replying(["<server_version><major>7</major><minor>24</minor><release>1</release></server_version>"]);
+        let last = ScriptedTransport::replying(["<server_version><major>8</major><minor>0</minor><release>2</release></server_version>"]);
+        let hosts = [("first", first.clone()), ("last", last.clone())];
+
+        let failed = run_on_all_hosts(&targets(&["first", "down", "last"]), connector(&hosts),
+            &CliCommand::ClientVersion, OnError::Stop, TimeStyle::default());
+
+        assert_eq!(failed, 1);
+        assert_eq!(first.request_tags(), ["exchange_versions"]);
+        assert!(last.requests().is_empty());
+    }
+
+    #[test]
+    fn counts_rejecting_hosts_as_failed() {
+        let hosts = [("rejecting", ScriptedTransport::replying(["<error>nope</error>"]))];
+
+        let failed = run_on_all_hosts(&targets(&["rejecting"]), connector(&hosts),
+            &CliCommand::ClientVersion, OnError::Continue, TimeStyle::default());
+
+        assert_eq!(failed, 1);
+    }
+
+    #[test]
+    fn suffixes_paths_with_host_name() {
+        let path = |path: &str| path_for_host(std::path::Path::new(path), "cruncher");
+        assert_eq!(path("state.xml"), std::path::Path::new("state.cruncher.xml"));
+        assert_eq!(path("/tmp/states/state"), std::path::Path::new("/tmp/states/state.cruncher"));
+        assert_eq!(path("backup.state.xml"), std::path::Path::new("backup.state.cruncher.xml"));
+    }
+
+    #[test]
+    fn tells_read_only_commands() {
+        assert!(is_read_only(&CliCommand::Status));
+        assert!(is_read_only(&CliCommand::GetFileTransfers { stalled: true, retry_stalled: false }));
+        assert!(!is_read_only(&CliCommand::GetFileTransfers { stalled: false, retry_stalled: true }));
+        assert!(is_read_only(&CliCommand::SaveState { path: "state.xml".into() }));
+        assert!(!is_read_only(&CliCommand::SuspendAllTasks));
+        assert!(!is_read_only(&CliCommand::Quit));
+    }
+
+    #[test]
+    fn filters_messages_by_priority() {
+        let info = Message { priority: MsgInfo::Info, ..Default::default() };
+        let alert = Message { priority: MsgInfo::UserAlert, ..Default::default() };
+        let error = Message { priority: MsgInfo::InternalError, ..Default::default() };
+
+        assert!(has_priority(&info, &[]));
+        assert!(has_priority(&alert, &[]));
+
+        let priorities = [MsgInfo::UserAlert, MsgInfo::InternalError];
+        assert!(!has_priority(&info, &priorities));
+        assert!(has_priority(&alert, &priorities));
+        assert!(has_priority(&error, &priorities));
+    }
+
+    #[test]
+    fn filters_notices_by_category() {
+        let news = Notice { category: "news".to_string(), ..Default::default() };
+        let scheduler = Notice { category: "scheduler".to_string(), ..Default::default() };
+        let server = Notice { category: "server".to_string(), ..Default::default() };
+
+        assert!(has_category(&news, &[]));
+        assert!(has_category(&server, &[]));
+
+        let categories = [SupportedNoticeCategory::News, SupportedNoticeCategory::Client];
+        assert!(has_category(&news, &categories));
+        assert!(!has_category(&scheduler, &categories));
+        assert!(!has_category(&server, &categories));
+        assert!(has_category(&scheduler, &[SupportedNoticeCategory::Scheduler]));
+    }
+
+    #[test]
+    fn displays_notice_title_and_link() {
+        let notice = Notice {
+            seqno: 3,
+            category: "news".to_string(),
+            project_name: "Einstein@Home".to_string(),
+            title: "Maintenance".to_string(),
+            description: " Down on Monday. ".to_string(),
+            link: "https://einsteinathome.org/news".to_string(),
+            ..Default::default()
+        };
+        let displayed = format!("{}", notice.display());
+
+        assert!(displayed.starts_with("3: ("));
+        assert!(displayed.contains(") [news] Einstein@Home: Maintenance\n   Down on Monday.\n"));
+        assert!(displayed.ends_with("\n   link: https://einsteinathome.org/news"));
+
+        let untitled = Notice { category: "client".to_string(), description: "Disk full".to_string(), ..Default::default() };
+        assert!(format!("{}", untitled.display()).ends_with(") [client] Disk full"));
+    }
+
+    #[test]
+    fn renders_notices_feed() {
+        let notices = [
+            Notice {
+                title: "Maintenance".to_string(),
+                project_name: "Einstein@Home".to_string(),
+                description: "Down on <b>Monday</b> & Tuesday".to_string(),
+                link: "https://einsteinathome.org/news".to_string(),
+                category: "news".to_string(),
+                create_time: Timestamp(1700000000.),
+                ..Default::default()
+            },
+            Notice { title: "Disk full".to_string(), ..Default::default() },
+        ];
+        let feed = notices_feed(&notices).unwrap();
+
+        assert!(feed.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<rss version=\"2.0\">\n  <channel>\n"));
+        assert!(feed.ends_with("  </channel>\n</rss>\n"));
+        assert_eq!(feed.matches("<item>").count(), 2);
+        assert!(feed.contains("\n    <item>\n      <title>Einstein@Home: Maintenance</title>\n"));
+        assert!(feed.contains("<description>Down on &lt;b&gt;Monday&lt;/b&gt; &amp; Tuesday</description>"));
+        assert!(feed.contains("<link>https://einsteinathome.org/news</link>"));
+        assert!(feed.contains("<category>news</category>"));
+        assert!(feed.contains("<pubDate>Tue, 14 Nov 2023 22:13:20 +0000</pubDate>"));
+        assert_eq!(feed.matches("<pubDate>").count(), 1);
+        assert_eq!(feed.matches("<link>").count(), 2);
+        assert!(libjoincserde::pretty_print(&feed).is_ok());
+    }
+
+    #[test]
+    fn matches_all_bodies_without_filter() {
+        let msg = Message { body: "Scheduler request failed".to_string(), ..Default::default() };
+        assert!(matches_body(&msg, None));
+    }
+
+    #[cfg(feature = "grep")]
+    #[test]
+    fn filters_messages_by_body() {
+        let msg = Message { body: "Scheduler request failed: HTTP error".to_string(), ..Default::default() };
+
+        assert!(matches_body(&msg, Some(&BodyFilter::new("scheduler.*failed", false).unwrap())));
+        assert!(!matches_body(&msg, Some(&BodyFilter::new("scheduler.*failed", true).unwrap())));
+        assert!(matches_body(&msg, Some(&BodyFilter::new("Scheduler.*failed", true).unwrap())));
+        assert!(!matches_body(&msg, Some(&BodyFilter::new("upload", false).unwrap())));
+        assert!(BodyFilter::new("(", false).is_err());
+    }
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(FormattedDuration(0.).to_string(), "---");
+        assert_eq!(FormattedDuration(f64::NAN).to_string(), "---");
+        assert_eq!(FormattedDuration(7.).to_string(), "7s");
+        assert_eq!(FormattedDuration(307.).to_string(), "5m 07s");
+        assert_eq!(FormattedDuration(3723.).to_string(), "1h 02m 03s");
+    }
+
+    #[test]
+    fn tabulates_disk_usage() {
+        let mut disk_usage = DiskUsageSummary { total: 4096., ..Default::default() };
+        disk_usage.projects.push(DiskUsageSummaryProject {
+            master_url: "https://a/".to_string(),
+            disk_usage: 1024.,
+            disk_share: 2048.,
+        });
+        let expected = "\
+master URL  disk usage  disk share  of total
+----------  ----------  ----------  --------
+https://a/     1.0 KiB     2.0 KiB     25.0%
+";
+
+        assert_eq!(disk_usage_table(&disk_usage).to_string(), expected);
+    }
+
+    #[test]
+    fn tabulates_tasks() {
+        let tasks = [
+            Task {
+                name: "running".to_string(),
+                project_url: "https://a/".to_string(),
+                state: ResultClientState::FilesDownloaded,
+                active_task: Some(ActiveTask { fraction_done: 0.25, ..Default::default() }),
+                report_deadline: Timestamp(1706933106.),
+                ..Default::default()
+            },
+            Task {
+                name: "queued".to_string(),
+                project_url: "https://b/".to_string(),
+                state: ResultClientState::New,
+                ..Default::default()
+            },
+        ];
+        let expected = "\
+name     project URL  state        done  report deadline
+-------  -----------  ----------  -----  ---------------
+running  https://a/   downloaded  25.0%  1706933106
+queued   https://b/   new                ---
+";
+
+        assert_eq!(tasks_table(&tasks, TimeStyle::Epoch).to_string(), expected);
+    }
+
+    #[test]
+    fn tabulates_project_list() {
+        let projects = [ProjectListEntry {
+            name: "Einstein@Home".to_string(),
+            general_area: "Physical Science".to_string(),
+            url: "https://einsteinathome.org/".to_string(),
+            ..Default::default()
+        }];
+        let expected = "\
+name           area              URL
+-------------  ----------------  ---------------------------
+Einstein@Home  Physical Science  https://einsteinathome.org/
+";
+
+        assert_eq!(project_list_table(&projects).to_string(), expected);
+    }
+
+    #[test]
+    fn renders_bars() {
+        assert_eq!(render_bar(0., 10), "[----------]");
+        assert_eq!(render_bar(0.5, 10), "[#####-----]");
+        assert_eq!(render_bar(1., 10), "[##########]");
+        assert_eq!(render_bar(1.5, 10), "[##########]");
+        assert_eq!(render_bar(fraction_of(1., 0.), 10), "[----------]");
+    }
+
+    #[test]
+    fn renders_versions_as_json() {
+        assert_eq!(
+            version_json().to_string(),
+            format!(r#"{{"joinccmd":"{}","libjoinc":"{}"}}"#, JOINCCMD_VERSION, libjoinc::VERSION)
+        );
+    }
+
+    #[test]
+    fn renders_successful_op_as_json() {
+        let outcome = CommandOutcome::new("project-op", "https://a.org/".to_string(), &Ok(()));
+        assert_eq!(serde_json::to_value(&outcome).unwrap(), serde_json::json!({
+            "ok": true,
+            "command": "project-op",
+            "target": "https://a.org/",
+        }));
+    }
+
+    #[test]
+    fn renders_failed_op_as_json() {
+        let result = Err(Error::Rejected("no such project".to_string(), -136));
+        let outcome = CommandOutcome::new("project-op", "https://a.org/".to_string(), &result);
+        assert_eq!(serde_json::to_value(&outcome).unwrap(), serde_json::json!({
+            "ok": false,
+            "command": "project-op",
+            "target": "https://a.org/",
+            "error": "Client error: no such project (error_num -136).",
+        }));
+    }
+
+    #[test]
+    fn renders_host_info_as_json() {
+        let host_info = HostInfo { p_fpops: 4.5e9, p_ncpus: 8, m_nbytes: 16e9, ..Default::default() };
+        let json = host_info_json(&host_info);
+
+        assert_eq!(json["cpu_gflops"], 4.5);
+        assert_eq!(json["cpu_count"], 8);
+        assert_eq!(json["memory_gb"], 16.);
+        assert_eq!(json["disk_free_gb"], 0.);
+    }
+
+    #[test]
+    fn writes_one_json_line_per_task() {
+        let tasks = vec![
+            Task { name: "a".to_string(), ..Default::default() },
+            Task { name: "b".to_string(), ..Default::default() },
+            Task { name: "c".to_string(), ..Default::default() },
+        ];
+        let mut buffer = vec![];
+
+        write_json_lines(&mut buffer, &tasks).unwrap();
+
+        let output = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].starts_with('{') && lines[1].contains(r#""name":"b""#));
+        assert!(lines[1].contains(r#""ready_to_report":false"#));
+    }
+}
@@ -0,0 +1,196 @@
+//! Plain text tables with aligned columns, e.g. for overviews of tasks or projects.
+
+use std::fmt;
+
+const SEPARATOR: &str = "  ";
+const ELLIPSIS: char = '…';
+
+/// The alignment of a column's cells.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+/// A table printing a header, a rule and its rows,
+/// shrinking the widest columns if it's wider than the maximum width.
+///
+/// Widths are counted in chars, i.e. wide glyphs may misalign a column.
+#[derive(Debug, Default)]
+pub struct Table {
+    columns: Vec<(String, Align)>,
+    rows: Vec<Vec<String>>,
+    max_width: Option<usize>,
+}
+
+impl Table {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a column with the given header.
+    pub fn column(mut self, header: &str, align: Align) -> Self {
+        self.columns.push((header.to_string(), align));
+        self
+    }
+
+    /// Limits the width of the lines, unlimited by default.
+    pub fn max_width(mut self, max_width: Option<usize>) -> Self {
+        self.max_width = max_width;
+        self
+    }
+
+    /// Adds a row, missing cells are left empty and extra cells are dropped.
+    pub fn row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    /// The width of each column after shrinking the table to the maximum width.
+    pub fn widths(&self) -> Vec<usize> {
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(idx, (header, _))| {
+                self.rows
+                    .iter()
+                    .filter_map(|row| row.get(idx))
+                    .chain([header])
+                    .map(|cell| cell.chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        if let Some(max_width) = self.max_width {
+            let separators = SEPARATOR.len() * widths.len().saturating_sub(1);
+            // shrink the widest column char by char, the rightmost one first on ties
+            while widths.iter().sum::<usize>() + separators > max_width {
+                match widths.iter().enumerate().max_by_key(|(_, &width)| width) {
+                    Some((idx, &width)) if width > 1 => widths[idx] -= 1,
+                    _ => break,
+                }
+            }
+        }
+
+        widths
+    }
+
+    fn write_line<'a>(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        widths: &[usize],
+        cells: impl Iterator<Item = &'a str>,
+    ) -> fmt::Result {
+        let mut line = String::new();
+        for (idx, (((_, align), &width), cell)) in self.columns.iter().zip(widths).zip(cells).enumerate() {
+            if idx > 0 {
+                line.push_str(SEPARATOR);
+            }
+            let cell = truncate(cell, width);
+            match align {
+                Align::Left => line.push_str(&format!("{cell:<width$}")),
+                Align::Right => line.push_str(&format!("{cell:>width$}")),
+            }
+        }
+        writeln!(f, "{}", line.trim_end())
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let widths = self.widths();
+        let rules: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+
+        self.write_line(f, &widths, self.columns.iter().map(|(header, _)| header.as_str()))?;
+        self.write_line(f, &widths, rules.iter().map(String::as_str))?;
+        for row in &self.rows {
+            let cells = row.iter().map(String::as_str).chain(std::iter::repeat(""));
+            self.write_line(f, &widths, cells)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The width of the terminal, falling back to the environment variable COLUMNS,
+/// e.g. if the output is piped, and None if both are unavailable.
+pub fn terminal_width() -> Option<usize> {
+    terminal_size::terminal_size()
+        .map(|(terminal_size::Width(width), _)| width as usize)
+        .or_else(|| std::env::var("COLUMNS").ok()?.parse().ok())
+}
+
+// cuts the cell to the width, ending in an ellipsis if cut
+fn truncate(cell: &str, width: usize) -> String {
+    if cell.chars().count() <= width {
+        cell.to_string()
+    } else {
+        cell.chars().take(width.saturating_sub(1)).chain([ELLIPSIS]).collect()
+    }
+}
+
+// ----- Tests -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(max_width: Option<usize>) -> Table {
+        let mut table = Table::new()
+            .column("name", Align::Left)
+            .column("URL", Align::Left)
+            .column("usage", Align::Right)
+            .max_width(max_width);
+        table.row(vec!["Einstein".to_string(), "https://einsteinathome.org/".to_string(), "1.5 GB".to_string()]);
+        table.row(vec!["Rosetta".to_string(), "https://boinc.bakerlab.org/".to_string(), "20 MB".to_string()]);
+        table
+    }
+
+    #[test]
+    fn computes_widths_of_widest_cells() {
+        assert_eq!(table(None).widths(), vec![8, 27, 6]);
+        assert_eq!(table(Some(100)).widths(), vec![8, 27, 6]);
+        assert_eq!(Table::new().column("header", Align::Left).widths(), vec![6]);
+    }
+
+    #[test]
+    fn shrinks_widest_columns_to_max_width() {
+        assert_eq!(table(Some(40)).widths(), vec![8, 22, 6]);
+        assert_eq!(table(Some(20)).widths(), vec![6, 5, 5]);
+        assert_eq!(table(Some(0)).widths(), vec![1, 1, 1]);
+    }
+
+    #[test]
+    fn aligns_cells() {
+        let expected = "\
+name      URL                           usage
+--------  ---------------------------  ------
+Einstein  https://einsteinathome.org/  1.5 GB
+Rosetta   https://boinc.bakerlab.org/   20 MB
+";
+        assert_eq!(table(None).to_string(), expected);
+    }
+
+    #[test]
+    fn truncates_cells_with_ellipsis() {
+        let expected = "\
+name      URL                      usage
+--------  ----------------------  ------
+Einstein  https://einsteinathom…  1.5 GB
+Rosetta   https://boinc.bakerla…   20 MB
+";
+        assert_eq!(table(Some(40)).to_string(), expected);
+        assert_eq!(truncate("abc", 3), "abc");
+        assert_eq!(truncate("abcd", 3), "ab…");
+        assert_eq!(truncate("abcd", 1), "…");
+    }
+
+    #[test]
+    fn leaves_missing_cells_empty() {
+        let mut table = Table::new().column("a", Align::Left).column("b", Align::Right);
+        table.row(vec!["x".to_string()]);
+
+        assert_eq!(table.to_string(), "a  b\n-  -\nx\n");
+    }
+}
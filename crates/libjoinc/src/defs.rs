@@ -1,9 +1,11 @@
 use serde::{Deserialize, Serialize};
-use serde_repr::Deserialize_repr;
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use crate::error::{Error, Result};
 use std::fmt::{self, Display};
+use std::str::FromStr;
 
-#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq, Eq)]
-#[serde(from = "DeserializedBool")]
+#[derive(Clone, Copy, Deserialize, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(from = "DeserializedBool", into = "bool")]
 pub enum Bool {
     #[default]
     False,
@@ -19,7 +21,7 @@ impl From<Bool> for bool {
 impl From<bool> for Bool {
     fn from(b: bool) -> Self {
         match b {
-            false => Bool::True,
+            false => Bool::False,
             true => Bool::True,
         }
     }
@@ -34,7 +36,7 @@ impl Display for Bool {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize_repr)]
 #[repr(i8)]
 pub enum ActiveTaskState {
     Uninitialized,
@@ -72,13 +74,35 @@ impl Display for ActiveTaskState {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum FileTransferOp {
     Abort,
     Retry,
 }
 
-#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq)]
+impl FileTransferOp {
+    /// Stable name of the file transfer op, accepted by `FromStr`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FileTransferOp::Abort => "abort",
+            FileTransferOp::Retry => "retry",
+        }
+    }
+}
+
+impl FromStr for FileTransferOp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "abort" => Ok(FileTransferOp::Abort),
+            "retry" => Ok(FileTransferOp::Retry),
+            _ => Err(Error::InvalidArgument(format!("unknown file transfer op: {}", s))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(i8)]
 pub enum MsgInfo {
     Info = 1,
@@ -100,7 +124,7 @@ impl Display for MsgInfo {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(i8)]
 pub enum NetworkStatus {
     Online,
@@ -124,7 +148,7 @@ impl Display for NetworkStatus {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum ProjectOp {
     Allowmorework,
     Detach,
@@ -137,7 +161,43 @@ pub enum ProjectOp {
     Update,
 }
 
-#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq)]
+impl ProjectOp {
+    /// Stable name of the project op, accepted by `FromStr`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProjectOp::Allowmorework => "allowmorework",
+            ProjectOp::Detach => "detach",
+            ProjectOp::DetachWhenDone => "detach-when-done",
+            ProjectOp::DontDetachWhenDone => "dont-detach-when-done",
+            ProjectOp::Nomorework => "nomorework",
+            ProjectOp::Reset => "reset",
+            ProjectOp::Resume => "resume",
+            ProjectOp::Suspend => "suspend",
+            ProjectOp::Update => "update",
+        }
+    }
+}
+
+impl FromStr for ProjectOp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "allowmorework" => Ok(ProjectOp::Allowmorework),
+            "detach" => Ok(ProjectOp::Detach),
+            "detach-when-done" => Ok(ProjectOp::DetachWhenDone),
+            "dont-detach-when-done" => Ok(ProjectOp::DontDetachWhenDone),
+            "nomorework" => Ok(ProjectOp::Nomorework),
+            "reset" => Ok(ProjectOp::Reset),
+            "resume" => Ok(ProjectOp::Resume),
+            "suspend" => Ok(ProjectOp::Suspend),
+            "update" => Ok(ProjectOp::Update),
+            _ => Err(Error::InvalidArgument(format!("unknown project op: {}", s))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize_repr)]
 #[repr(i8)]
 pub enum ResultClientState {
     New,
@@ -169,7 +229,7 @@ impl Display for ResultClientState {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize_repr)]
 #[repr(i8)]
 pub enum RpcReason {
     None,
@@ -201,7 +261,7 @@ impl Display for RpcReason {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Deserialize_repr, Eq, PartialEq, Serialize)]
+#[derive(Clone, Copy, Debug, Default, Deserialize_repr, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize)]
 #[serde(rename_all = "lowercase")]
 #[repr(i8)]
 pub enum RunMode {
@@ -227,7 +287,34 @@ impl Display for RunMode {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq)]
+impl RunMode {
+    /// Stable name of the run mode, accepted by `FromStr`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            RunMode::Always => "always",
+            RunMode::Auto => "auto",
+            RunMode::Never => "never",
+            RunMode::Restore => "restore",
+            RunMode::UnknownToJoinc => "unknown",
+        }
+    }
+}
+
+impl FromStr for RunMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "always" => Ok(RunMode::Always),
+            "auto" => Ok(RunMode::Auto),
+            "never" => Ok(RunMode::Never),
+            "restore" => Ok(RunMode::Restore),
+            _ => Err(Error::InvalidArgument(format!("unknown run mode: {}", s))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize_repr)]
 #[repr(i8)]
 pub enum SchedulerState {
     Uninitialized,
@@ -249,7 +336,55 @@ impl Display for SchedulerState {
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq)]
+/// The modes the client can be set to, unlike [RunMode] without the unknown one.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum SettableRunMode {
+    Always,
+    Auto,
+    Never,
+    Restore,
+}
+
+impl From<SettableRunMode> for RunMode {
+    fn from(mode: SettableRunMode) -> Self {
+        match mode {
+            SettableRunMode::Always => RunMode::Always,
+            SettableRunMode::Auto => RunMode::Auto,
+            SettableRunMode::Never => RunMode::Never,
+            SettableRunMode::Restore => RunMode::Restore,
+        }
+    }
+}
+
+impl TryFrom<RunMode> for SettableRunMode {
+    type Error = Error;
+
+    fn try_from(mode: RunMode) -> Result<Self> {
+        match mode {
+            RunMode::Always => Ok(SettableRunMode::Always),
+            RunMode::Auto => Ok(SettableRunMode::Auto),
+            RunMode::Never => Ok(SettableRunMode::Never),
+            RunMode::Restore => Ok(SettableRunMode::Restore),
+            RunMode::UnknownToJoinc => Err(Error::InvalidArgument("unknown run mode".to_string())),
+        }
+    }
+}
+
+impl FromStr for SettableRunMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "always" => Ok(SettableRunMode::Always),
+            "auto" => Ok(SettableRunMode::Auto),
+            "never" => Ok(SettableRunMode::Never),
+            "restore" => Ok(SettableRunMode::Restore),
+            _ => Err(Error::InvalidArgument(format!("unknown run mode: {}", s))),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize_repr, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[repr(i32)]
 pub enum SuspendReason {
     NotSuspended = 0,
@@ -301,13 +436,37 @@ impl Display for SuspendReason {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum TaskOp {
     Abort,
     Resume,
     Suspend,
 }
 
+impl TaskOp {
+    /// Stable name of the task op, accepted by `FromStr`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            TaskOp::Abort => "abort",
+            TaskOp::Resume => "resume",
+            TaskOp::Suspend => "suspend",
+        }
+    }
+}
+
+impl FromStr for TaskOp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "abort" => Ok(TaskOp::Abort),
+            "resume" => Ok(TaskOp::Resume),
+            "suspend" => Ok(TaskOp::Suspend),
+            _ => Err(Error::InvalidArgument(format!("unknown task op: {}", s))),
+        }
+    }
+}
+
 // ----- deserialization helper -----
 
 #[derive(Deserialize, Debug, PartialEq, Eq)]
@@ -356,4 +515,69 @@ mod tests {
 
         assert_eq!(deserialized, expected);
     }
+
+    #[test]
+    fn converts_booleans() {
+        assert_eq!(Bool::from(false), Bool::False);
+        assert_eq!(Bool::from(true), Bool::True);
+        assert!(!bool::from(Bool::from(false)));
+        assert!(bool::from(Bool::from(true)));
+    }
+
+    #[test]
+    fn converts_settable_run_modes() {
+        assert_eq!(RunMode::from(SettableRunMode::Always), RunMode::Always);
+        assert_eq!(RunMode::from(SettableRunMode::Auto), RunMode::Auto);
+        assert_eq!(RunMode::from(SettableRunMode::Never), RunMode::Never);
+        assert_eq!(RunMode::from(SettableRunMode::Restore), RunMode::Restore);
+
+        assert_eq!(SettableRunMode::try_from(RunMode::Never).unwrap(), SettableRunMode::Never);
+        assert!(SettableRunMode::try_from(RunMode::UnknownToJoinc).is_err());
+
+        assert_eq!("auto".parse::<SettableRunMode>().unwrap(), SettableRunMode::Auto);
+        assert!(matches!("unknown".parse::<SettableRunMode>(), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn orders_states_by_discriminant() {
+        assert!(ResultClientState::UnknownToJoinc < ResultClientState::New);
+        assert!(ResultClientState::New < ResultClientState::FilesDownloaded);
+        assert!(ResultClientState::FilesDownloaded < ResultClientState::ComputeError);
+        assert!(SchedulerState::Uninitialized < SchedulerState::Scheduled);
+        assert!(SuspendReason::Os < SuspendReason::WifiState);
+        assert!(RunMode::Always < RunMode::Never);
+    }
+
+    #[test]
+    fn keys_maps_by_state() {
+        let mut counts = std::collections::HashMap::new();
+        for state in [ActiveTaskState::Executing, ActiveTaskState::Suspended, ActiveTaskState::Executing] {
+            *counts.entry(state).or_insert(0) += 1;
+        }
+
+        assert_eq!(counts[&ActiveTaskState::Executing], 2);
+        assert_eq!(counts[&ActiveTaskState::Suspended], 1);
+    }
+
+    #[test]
+    fn parses_op_names() {
+        for op in [FileTransferOp::Abort, FileTransferOp::Retry] {
+            assert_eq!(op.name().parse::<FileTransferOp>().unwrap(), op);
+        }
+        for op in [ProjectOp::Allowmorework, ProjectOp::DetachWhenDone, ProjectOp::DontDetachWhenDone, ProjectOp::Update] {
+            assert_eq!(op.name().parse::<ProjectOp>().unwrap(), op);
+        }
+        for op in [TaskOp::Abort, TaskOp::Resume, TaskOp::Suspend] {
+            assert_eq!(op.name().parse::<TaskOp>().unwrap(), op);
+        }
+        assert!(matches!("detach_when_done".parse::<ProjectOp>(), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn parses_run_mode_names() {
+        for mode in [RunMode::Always, RunMode::Auto, RunMode::Never, RunMode::Restore] {
+            assert_eq!(mode.name().parse::<RunMode>().unwrap(), mode);
+        }
+        assert!(matches!(RunMode::UnknownToJoinc.name().parse::<RunMode>(), Err(Error::InvalidArgument(_))));
+    }
 }
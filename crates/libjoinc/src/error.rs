@@ -2,7 +2,9 @@
 pub enum Error {
     Client(String),
     Deserialization(libjoincserde::Error),
+    InvalidArgument(String),
     Io(std::io::Error),
+    Rejected(String, i32),
     Rpc(String),
     Unauthorized,
 }
@@ -0,0 +1,107 @@
+//! Formatting of the client's timestamps for humans and other programs.
+
+use crate::types::Timestamp;
+use chrono::{DateTime, Local, SecondsFormat, Utc};
+
+/// How to format a [Timestamp].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TimeStyle {
+    /// The local time, e.g. `03-Feb-2024 04:05:06`.
+    #[default]
+    Absolute,
+    /// RFC 3339 in UTC, e.g. `2024-02-03T04:05:06Z`.
+    Iso8601,
+    /// The seconds since the epoch, e.g. `1706933106`.
+    Epoch,
+    /// The time relative to now, e.g. `1h 02m ago` or `in 5m 03s`.
+    Relative,
+}
+
+/// Formats the timestamp in the given style, None if it's unset.
+pub fn format_timestamp(timestamp: Timestamp, style: TimeStyle) -> Option<String> {
+    format_timestamp_at(timestamp, style, Timestamp(Utc::now().timestamp() as f64))
+}
+
+/// Like [format_timestamp], but relative to `now` instead of the current time.
+pub fn format_timestamp_at(timestamp: Timestamp, style: TimeStyle, now: Timestamp) -> Option<String> {
+    let time = Some(timestamp.0)
+        .filter(|&t| t > 0.)
+        .and_then(|t| DateTime::from_timestamp(t as i64, 0))?;
+    Some(match style {
+        TimeStyle::Absolute => time.with_timezone(&Local).format("%d-%b-%Y %H:%M:%S").to_string(),
+        TimeStyle::Iso8601 => time.to_rfc3339_opts(SecondsFormat::Secs, true),
+        TimeStyle::Epoch => timestamp.0.to_string(),
+        TimeStyle::Relative => {
+            let offset = now.0 - timestamp.0;
+            let span = format_span(offset.abs().round() as u64);
+            if offset < 0. {
+                format!("in {span}")
+            } else {
+                format!("{span} ago")
+            }
+        }
+    })
+}
+
+// the two most significant units, e.g. 2d 03h
+fn format_span(secs: u64) -> String {
+    let (days, hours, mins, secs) = (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{days}d {hours:02}h")
+    } else if hours > 0 {
+        format!("{hours}h {mins:02}m")
+    } else if mins > 0 {
+        format!("{mins}m {secs:02}s")
+    } else {
+        format!("{secs}s")
+    }
+}
+
+// ----- Tests -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2024-02-03T04:05:06Z
+    const TIME: Timestamp = Timestamp(1706933106.);
+
+    #[test]
+    fn formats_absolute_in_local_time() {
+        let expected = DateTime::from_timestamp(1706933106, 0)
+            .unwrap()
+            .with_timezone(&Local)
+            .format("%d-%b-%Y %H:%M:%S")
+            .to_string();
+        assert_eq!(format_timestamp(TIME, TimeStyle::Absolute), Some(expected));
+    }
+
+    #[test]
+    fn formats_iso8601_in_utc() {
+        assert_eq!(format_timestamp(TIME, TimeStyle::Iso8601).as_deref(), Some("2024-02-03T04:05:06Z"));
+    }
+
+    #[test]
+    fn formats_epoch_as_raw_seconds() {
+        assert_eq!(format_timestamp(TIME, TimeStyle::Epoch).as_deref(), Some("1706933106"));
+        assert_eq!(format_timestamp(Timestamp(1706933106.5), TimeStyle::Epoch).as_deref(), Some("1706933106.5"));
+    }
+
+    #[test]
+    fn formats_relative_to_now() {
+        let relative = |now: f64| format_timestamp_at(TIME, TimeStyle::Relative, Timestamp(TIME.0 + now)).unwrap();
+        assert_eq!(relative(0.), "0s ago");
+        assert_eq!(relative(42.), "42s ago");
+        assert_eq!(relative(3723.), "1h 02m ago");
+        assert_eq!(relative(2. * 86400. + 3. * 3600.), "2d 03h ago");
+        assert_eq!(relative(-303.), "in 5m 03s");
+    }
+
+    #[test]
+    fn skips_unset_timestamps() {
+        for style in [TimeStyle::Absolute, TimeStyle::Iso8601, TimeStyle::Epoch, TimeStyle::Relative] {
+            assert_eq!(format_timestamp(Timestamp(0.), style), None);
+            assert_eq!(format_timestamp(Timestamp(-1.), style), None);
+        }
+    }
+}
@@ -1,4 +1,22 @@
 pub mod defs;
 pub mod error;
+#[cfg(feature = "chrono")]
+pub mod format;
+pub mod notices;
+#[cfg(feature = "net")]
 pub mod rpc;
 pub mod types;
+
+/// The version of libjoinc.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+
+// ----- Tests -----
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn exposes_crate_version() {
+        assert_eq!(super::VERSION, env!("CARGO_PKG_VERSION"));
+        assert!(!super::VERSION.is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+//! Helpers for consumers polling the notices of a client over time.
+
+use crate::types::Notice;
+use std::collections::HashSet;
+
+// the client re-sends notices, identical ones share these
+type NoticeKey = (i32, u64, String);
+
+fn key(notice: &Notice) -> NoticeKey {
+    (notice.seqno, notice.create_time.0.to_bits(), notice.title.clone())
+}
+
+/// Appends the notices of a poll to the ones seen before, skipping the ones already seen,
+/// identified by their sequence number, creation time and title.
+pub fn dedup_notices(existing: &mut Vec<Notice>, new: Vec<Notice>) {
+    let mut seen: HashSet<NoticeKey> = existing.iter().map(key).collect();
+    existing.extend(new.into_iter().filter(|notice| seen.insert(key(notice))));
+}
+
+// ----- Tests -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Timestamp;
+
+    fn notice(seqno: i32, create_time: f64, title: &str) -> Notice {
+        Notice {
+            seqno,
+            create_time: Timestamp(create_time),
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn seqnos(notices: &[Notice]) -> Vec<i32> {
+        notices.iter().map(|notice| notice.seqno).collect()
+    }
+
+    #[test]
+    fn skips_notices_of_overlapping_polls() {
+        let mut notices = vec![];
+        dedup_notices(&mut notices, (1..=3).map(|seqno| notice(seqno, 100., "a")).collect());
+        dedup_notices(&mut notices, (2..=5).map(|seqno| notice(seqno, 100., "a")).collect());
+        dedup_notices(&mut notices, (1..=5).map(|seqno| notice(seqno, 100., "a")).collect());
+
+        assert_eq!(seqnos(&notices), [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn skips_duplicates_within_a_poll() {
+        let mut notices = vec![notice(1, 100., "a")];
+        dedup_notices(&mut notices, vec![notice(2, 100., "b"), notice(2, 100., "b"), notice(1, 100., "a")]);
+
+        assert_eq!(seqnos(&notices), [1, 2]);
+    }
+
+    #[test]
+    fn keeps_notices_differing_in_time_or_title() {
+        let mut notices = vec![notice(1, 100., "a")];
+        dedup_notices(&mut notices, vec![notice(1, 200., "a"), notice(1, 100., "b")]);
+
+        assert_eq!(notices.len(), 3);
+    }
+}
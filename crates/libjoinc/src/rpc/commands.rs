@@ -1,12 +1,73 @@
 use crate::error::{Error, Result};
-use crate::rpc::connection::Connection;
+use crate::rpc::connection::RpcTransport;
 use crate::types::*;
 use crate::defs::*;
-use libjoincserde::{from_str, to_vec};
+use libjoincserde::{from_str, to_vec, to_vec_with_capacity};
+use serde::de::IgnoredAny;
 use serde::{Deserialize, Serialize};
 
+// The get_cc_config and set_cc_config RPCs were added with the client's event log options in 7.2.
+const CC_CONFIG_CLIENT_VERSION: Version = Version { major: 7, minor: 2, release: 0 };
+// The notices were added in 6.11.
+const NOTICES_CLIENT_VERSION: Version = Version { major: 6, minor: 11, release: 0 };
+
+#[cfg(feature = "async")]
+mod asynchronous;
+#[cfg(feature = "async")]
+pub use self::asynchronous::AsyncCommand;
+
 pub trait Command<RESP> {
-    fn execute(&mut self, connection: &mut Connection) -> Result<RESP>;
+    /// The request sent to the client, without the envelope.
+    fn request(&self) -> Result<Vec<u8>>;
+
+    /// Parses the client's reply to the request, failing if the client rejected it.
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<RESP>;
+
+    fn execute(&mut self, connection: &mut dyn RpcTransport) -> Result<RESP> {
+        let reply = connection.do_rpc(&self.request()?)?;
+        self.parse_reply(reply)
+    }
+
+    /// Whether executing the command doesn't change the client's state,
+    /// i.e. it's safe to retry it.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
+    /// The oldest client version knowing the command, None if every supported client knows it.
+    fn required_client_version(&self) -> Option<Version> {
+        None
+    }
+
+    /// Fails with `Error::Client("requires client >= X")` if the client of the given version,
+    /// e.g. as reported by [ExchangeVersionsCommand], doesn't know the command.
+    fn check_client_version(&self, client_version: &Version) -> Result<()> {
+        match self.required_client_version() {
+            Some(required) => client_version.requires_at_least(required),
+            None => Ok(()),
+        }
+    }
+
+    /// Executes the command if the client of the given version knows it,
+    /// instead of failing with whatever an older client replies to an unknown command.
+    fn execute_for(&mut self, connection: &mut dyn RpcTransport, client_version: &Version) -> Result<RESP> {
+        self.check_client_version(client_version)?;
+        self.execute(connection)
+    }
+
+    /// Executes the command, failing with `Error::Rpc("command timed out")` if it takes longer
+    /// than the timeout in total, e.g. of a hanging client or one trickling its reply.
+    /// The connection is out of sync after a timeout, so it fails any further exchange.
+    fn execute_within(&mut self, connection: &mut dyn RpcTransport, timeout: std::time::Duration) -> Result<RESP> {
+        connection.set_deadline(Some(std::time::Instant::now() + timeout))?;
+        let result = self.execute(connection).map_err(|err| match err {
+            Error::Io(io_err) if matches!(io_err.kind(),
+                std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock) => Error::Rpc("command timed out".to_string()),
+            err => err,
+        });
+        let reset = connection.set_deadline(None);
+        result.and_then(|response| reset.map(|_| response))
+    }
 }
 
 #[derive(Deserialize)]
@@ -19,7 +80,17 @@ struct UnauthorizedReply {
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
 struct ErrorReply {
-    error: String,
+    error: ErrorDto,
+    error_num: Option<i32>,
+}
+
+// the error_num is reported either as child or as sibling of the error message
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ErrorDto {
+    #[serde(default, rename = "$text")]
+    message: String,
+    error_num: Option<i32>,
 }
 
 #[derive(Deserialize)]
@@ -29,57 +100,266 @@ struct SuccessReply {
     _success: String,
 }
 
-fn execute_preprocessed_rpc_operation<REQ, RESP, PREPROCESSOR>(connection: &mut Connection, request: &REQ, pre_processor: PREPROCESSOR) -> Result<RESP>
+const ROOT_OPENING: &str = "<root>";
+const ROOT_CLOSING: &str = "</root>";
+
+fn deserialize_preprocessed_reply<RESP, PREPROCESSOR>(raw_response: Vec<u8>, pre_processor: PREPROCESSOR) -> Result<RESP>
 where
-    REQ: Serialize,
     RESP: for<'de> Deserialize<'de>,
     PREPROCESSOR: Fn(String) -> String
 {
-    let raw_response = connection.do_rpc(&to_vec(request)?)?;
-    let pre_processed = pre_processor(String::from_utf8(raw_response)
-        .map_err(|_| Error::Rpc("Recieved a non-UTF8 response from the client".to_string()))?);
+    let pre_processed = pre_processor(decode_reply(raw_response)?);
+    deserialize_reply(&wrap_in_root(pre_processed))
+}
+
+// The root tag is a workaround for proper expected tag matching during deserialization.
+// It's added in place, the reply has the room of the stripped envelope to spare.
+fn wrap_in_root(mut reply: String) -> String {
+    reply.reserve(ROOT_OPENING.len() + ROOT_CLOSING.len());
+    reply.insert_str(0, ROOT_OPENING);
+    reply.push_str(ROOT_CLOSING);
+    reply
+}
+
+// the team of a project is wrapped in an ifteam tag, which the types don't model
+const PROJECT_WRAPPER_TAGS: &[&str] = &["ifteam"];
+
+// Makes a preprocessor stripping the tags which wrap parts of a reply, keeping the wrapped content.
+// Replies without any of the tags are passed through untouched.
+fn strip_wrapper_tags(tags: &'static [&'static str]) -> impl Fn(String) -> String + Send {
+    move |mut reply| {
+        for tag in tags {
+            for wrapper in [format!("<{}>", tag), format!("</{}>", tag), format!("<{}/>", tag)] {
+                if reply.contains(&wrapper) {
+                    reply = reply.replace(&wrapper, "");
+                }
+            }
+        }
+        reply
+    }
+}
 
-    // the root tag is a workaround for proper expected tag matching during deserialization
-    let response = "<root>".to_string() + &pre_processed + "</root>";
-    match from_str(&response) {
-        Ok(deserialized) => Ok(deserialized),
-        Err(de_err) => match from_str::<ErrorReply>(&response) {
-            Ok(error) => Err(Error::Client(error.error)),
-            _ => match from_str::<UnauthorizedReply>(&response) {
-                Ok(_) => Err(Error::Unauthorized),
-                _ => Err(Error::Deserialization(de_err)),
-            },
-        },
+// the client may prepend a byte order mark, which isn't part of the document
+fn decode_reply(mut raw_response: Vec<u8>) -> Result<String> {
+    if raw_response.starts_with(b"\xef\xbb\xbf") {
+        raw_response.drain(..3);
     }
+    String::from_utf8(raw_response).map_err(|err| Error::Rpc(format!(
+        "Received a non-UTF-8 reply from the client, invalid byte at offset {}",
+        err.utf8_error().valid_up_to())))
 }
 
-fn execute_rpc_operation<REQ, RESP>(connection: &mut Connection, request: &REQ) -> Result<RESP>
+// The error shapes are checked first, so a rejected request doesn't end up
+// in a confusing error about the reply not matching the expected type.
+// Both reply types deny unknown fields, so they fail fast on any other reply.
+fn deserialize_reply<RESP>(response: &str) -> Result<RESP>
 where
-    REQ: Serialize,
     RESP: for<'de> Deserialize<'de>,
 {
-    execute_preprocessed_rpc_operation(connection, request, |s| s)
+    if from_str::<UnauthorizedReply>(response).is_ok() {
+        return Err(Error::Unauthorized);
+    }
+
+    if let Ok(reply) = from_str::<ErrorReply>(response) {
+        return Err(match reply.error.error_num.or(reply.error_num) {
+            Some(error_num) => Error::Rejected(reply.error.message, error_num),
+            None => Error::Client(reply.error.message),
+        });
+    }
+
+    Ok(from_str(response)?)
+}
+
+fn deserialize_raw_reply<RESP>(raw_response: Vec<u8>) -> Result<RESP>
+where
+    RESP: for<'de> Deserialize<'de>,
+{
+    deserialize_preprocessed_reply(raw_response, |s| s)
+}
+
+/// Sends the command's request and returns the reply without deserializing it, e.g. for saving it.
+/// Fails like the commands if the client rejects the request.
+pub fn fetch_raw_reply<REQ>(connection: &mut dyn RpcTransport, request: &REQ) -> Result<String>
+where
+    REQ: Serialize,
+{
+    let mut reply = wrap_in_root(decode_reply(connection.do_rpc(&to_vec(request)?)?)?);
+    let _: IgnoredAny = deserialize_reply(&reply)?;
+    reply.truncate(reply.len() - ROOT_CLOSING.len());
+    reply.drain(..ROOT_OPENING.len());
+    Ok(reply)
+}
+
+/// Normalizes a project url the way BOINC expects it to match the master url,
+/// i.e. with lowercase scheme and host and a trailing slash.
+pub fn normalize_project_url(project_url: &str) -> String {
+    let (scheme, rest) = match project_url.find("://") {
+        Some(idx) => project_url.split_at(idx + 3),
+        None => ("", project_url),
+    };
+    let (host, path) = match rest.find('/') {
+        Some(idx) => rest.split_at(idx),
+        None => (rest, ""),
+    };
+
+    let mut normalized = scheme.to_lowercase() + &host.to_lowercase() + path;
+    if !normalized.ends_with('/') {
+        normalized.push('/');
+    }
+    normalized
+}
+
+// ----- retrying -----
+
+pub struct RetryPolicy {
+    /// Number of attempts including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for each further retry
+    pub base_delay: std::time::Duration,
+    /// Whether to retry commands changing the client's state too
+    pub retry_mutating: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: std::time::Duration::from_millis(200),
+            retry_mutating: false,
+        }
+    }
+}
+
+/// Executes the command on a connection opened by `connect` and authorized with the password, if any,
+/// retrying it with exponential backoff on transient I/O failures, e.g. a timeout.
+///
+/// Each attempt opens a new connection, as a failed exchange may leave (the rest of) its reply
+/// in the old one, which a retried request would read instead of its own.
+pub fn execute_with_retry<C, RESP, T, F>(command: &mut C, mut connect: F, password: Option<&str>, policy: &RetryPolicy) -> Result<RESP>
+where
+    C: Command<RESP>,
+    T: RpcTransport,
+    F: FnMut() -> Result<T>,
+{
+    let retryable = command.is_read_only() || policy.retry_mutating;
+    let mut attempt = 1;
+    loop {
+        let result = connect().and_then(|mut connection| {
+            if let Some(password) = password {
+                AuthorizeCommand::new(password).execute(&mut connection)?;
+            }
+            command.execute(&mut connection)
+        });
+        match result {
+            Err(err) if retryable && attempt < policy.max_attempts && is_transient(&err) => {
+                std::thread::sleep(policy.base_delay * 2u32.pow(attempt - 1));
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+// interrupted, timed out or dropped exchanges, while the errors of the client
+// and replies of something else than a client won't change on retrying
+fn is_transient(err: &Error) -> bool {
+    match err {
+        Error::Io(io_err) => matches!(io_err.kind(),
+            std::io::ErrorKind::Interrupted
+                | std::io::ErrorKind::TimedOut
+                | std::io::ErrorKind::WouldBlock
+                | std::io::ErrorKind::ConnectionReset
+                | std::io::ErrorKind::ConnectionAborted
+                | std::io::ErrorKind::BrokenPipe
+                | std::io::ErrorKind::UnexpectedEof),
+        _ => false,
+    }
+}
+
+// ----- polling -----
+
+/// Source of time for polling, replaceable to test polling without sleeping.
+pub trait Clock {
+    fn now(&self) -> std::time::Instant;
+    fn sleep(&self, duration: std::time::Duration);
+}
+
+#[derive(Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> std::time::Instant {
+        std::time::Instant::now()
+    }
+
+    fn sleep(&self, duration: std::time::Duration) {
+        std::thread::sleep(duration)
+    }
+}
+
+pub struct PollOptions {
+    /// Time after which polling is given up, None to poll until there's a result
+    pub overall_timeout: Option<std::time::Duration>,
+    /// Delay between two polls
+    pub interval: std::time::Duration,
+}
+
+impl Default for PollOptions {
+    fn default() -> Self {
+        Self {
+            overall_timeout: Some(std::time::Duration::from_secs(60)),
+            interval: std::time::Duration::from_secs(1),
+        }
+    }
+}
+
+/// Calls `poll` until it yields a result, e.g. for the second phase of the client's two-phase operations.
+/// Returns None if there's no result within the overall timeout.
+pub fn poll_until<T, F>(options: &PollOptions, clock: &impl Clock, mut poll: F) -> Result<Option<T>>
+where
+    F: FnMut() -> Result<Option<T>>,
+{
+    let deadline = options.overall_timeout.map(|timeout| clock.now() + timeout);
+    loop {
+        if let Some(result) = poll()? {
+            return Ok(Some(result));
+        }
+        if deadline.is_some_and(|deadline| clock.now() + options.interval > deadline) {
+            return Ok(None);
+        }
+        clock.sleep(options.interval);
+    }
 }
 
 // ----- AuthorizeCommand -----
 
+/// First step of the authorization, requesting the nonce.
 #[derive(Default, Deserialize, Serialize)]
 #[serde(rename(serialize = "auth1"))]
-struct Auth1Operation {
+pub struct Auth1Operation {
     #[serde(skip_serializing)]
     nonce: String,
 }
 
 impl Command<String> for Auth1Operation {
-    fn execute(&mut self, connection: &mut Connection) -> Result<String> {
-        let response: Auth1Operation = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<String> {
+        let response: Auth1Operation = deserialize_raw_reply(reply)?;
         Ok(response.nonce)
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
+/// Second step of the authorization, sending the hashed nonce.
 #[derive(Deserialize, Serialize)]
 #[serde(rename(serialize = "auth2"))]
-struct Auth2Operation {
+pub struct Auth2Operation {
     #[serde(skip_serializing)]
     authorized: Option<String>,
     #[serde(skip_deserializing)]
@@ -87,42 +367,80 @@ struct Auth2Operation {
 }
 
 impl Auth2Operation {
-    fn new(password: &str, nonce: &str) -> Self {
+    /// Uses an externally computed hash, which the client expects to be
+    /// the lowercase hex encoded md5 of the nonce followed by the password.
+    pub fn with_hash(nonce_hash: String) -> Self {
         Self {
             authorized: None,
-            nonce_hash: format!("{:x}", md5::compute(nonce.to_owned() + password)),
+            nonce_hash,
         }
     }
 }
 
 impl Command<bool> for Auth2Operation {
-    fn execute(&mut self, connection: &mut Connection) -> Result<bool> {
-        let response: Auth2Operation = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<bool> {
+        let response: Auth2Operation = deserialize_raw_reply(reply)?;
         Ok(response.authorized.is_some())
     }
 }
 
-pub struct AuthorizeCommand {
+/// Computes the hash of the nonce and password sent to the client.
+pub trait NonceHasher {
+    fn hash(&self, nonce: &str, password: &str) -> String;
+}
+
+/// The hashing used by the current BOINC clients, md5 of the nonce followed by the password.
+#[derive(Default)]
+pub struct Md5Hasher;
+
+impl NonceHasher for Md5Hasher {
+    fn hash(&self, nonce: &str, password: &str) -> String {
+        format!("{:x}", md5::compute(nonce.to_owned() + password))
+    }
+}
+
+pub struct AuthorizeCommand<H = Md5Hasher> {
     password: String,
+    hasher: H,
 }
 
 impl AuthorizeCommand {
     pub fn new<T>(password: T) -> Self
+    where
+        T: Into<String>,
+    {
+        Self::with_hasher(password, Md5Hasher)
+    }
+}
+
+impl<H> AuthorizeCommand<H>
+where
+    H: NonceHasher,
+{
+    pub fn with_hasher<T>(password: T, hasher: H) -> Self
     where
         T: Into<String>,
     {
         AuthorizeCommand {
             password: password.into(),
+            hasher,
         }
     }
-}
 
-impl Command<()> for AuthorizeCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<()> {
+    fn auth2_operation(&self, nonce: &str) -> Auth2Operation {
+        Auth2Operation::with_hash(self.hasher.hash(nonce, &self.password))
+    }
+
+    /// Authorizes the connection, which takes the exchanges of both steps, so it isn't a single [Command].
+    pub fn execute(&mut self, connection: &mut dyn RpcTransport) -> Result<()> {
         let mut auth1 = Auth1Operation::default();
         let nonce = auth1.execute(connection)?;
 
-        let mut auth2 = Auth2Operation::new(&self.password, &nonce);
+        let mut auth2 = self.auth2_operation(&nonce);
         if auth2.execute(connection)? {
             Ok(())
         } else {
@@ -147,10 +465,18 @@ impl ExchangeVersionsCommand {
 }
 
 impl Command<Version> for ExchangeVersionsCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<Version> {
-        let response: Self = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<Version> {
+        let response: Self = deserialize_raw_reply(reply)?;
         Ok(response.version)
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ----- FileTransferOpCommand -----
@@ -171,6 +497,10 @@ pub struct FileTransferOpCommand {
 
 impl FileTransferOpCommand {
     pub fn new(project_url: String, filename: String, op: FileTransferOp) -> Self {
+        Self::new_verbatim(normalize_project_url(&project_url), filename, op)
+    }
+
+    pub fn new_verbatim(project_url: String, filename: String, op: FileTransferOp) -> Self {
         Self {
             dto: match op {
                 FileTransferOp::Abort => FileTransferOpDto::Abort { project_url, filename },
@@ -181,12 +511,46 @@ impl FileTransferOpCommand {
 }
 
 impl Command<()> for FileTransferOpCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<()> {
-        let _: SuccessReply = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
         Ok(())
     }
 }
 
+// ----- GetAllProjectsListCommand -----
+
+#[derive(Default, Deserialize)]
+struct ProjectListDto {
+    project: Option<Vec<ProjectListEntry>>,
+}
+
+/// Gets the list of all projects known to BOINC, which may be attached to.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename(serialize = "get_all_projects_list"))]
+pub struct GetAllProjectsListCommand {
+    #[serde(skip_serializing)]
+    projects: ProjectListDto,
+}
+
+impl Command<Vec<ProjectListEntry>> for GetAllProjectsListCommand {
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<Vec<ProjectListEntry>> {
+        let response: Self = deserialize_raw_reply(reply)?;
+        Ok(response.projects.project.unwrap_or_default())
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+}
+
 // ----- GetCCStatusCommand -----
 
 #[derive(Default, Deserialize, Serialize)]
@@ -197,10 +561,47 @@ pub struct GetCCStatusCommand {
 }
 
 impl Command<CCStatus> for GetCCStatusCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<CCStatus> {
-        let response: Self = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<CCStatus> {
+        let response: Self = deserialize_raw_reply(reply)?;
         Ok(response.cc_status)
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+}
+
+// ----- GetCcConfigCommand -----
+
+/// Gets the client's cc_config.xml, i.e. the log flags and options.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename(serialize = "get_cc_config"))]
+pub struct GetCcConfigCommand {
+    #[serde(skip_serializing)]
+    cc_config: CcConfigFile,
+}
+
+impl Command<CcConfigFile> for GetCcConfigCommand {
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<CcConfigFile> {
+        let response: Self = deserialize_raw_reply(reply)?;
+        Ok(response.cc_config)
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn required_client_version(&self) -> Option<Version> {
+        Some(CC_CONFIG_CLIENT_VERSION)
+    }
 }
 
 // ----- GetDiskUsageSummaryCommand -----
@@ -213,10 +614,18 @@ pub struct GetDiskUsageSummaryCommand {
 }
 
 impl Command<DiskUsageSummary> for GetDiskUsageSummaryCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<DiskUsageSummary> {
-        let response: Self = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<DiskUsageSummary> {
+        let response: Self = deserialize_raw_reply(reply)?;
         Ok(response.disk_usage_summary)
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ----- GetHostInfoCommand -----
@@ -229,10 +638,18 @@ pub struct GetHostInfoCommand {
 }
 
 impl Command<HostInfo> for GetHostInfoCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<HostInfo> {
-        let response: Self = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<HostInfo> {
+        let response: Self = deserialize_raw_reply(reply)?;
         Ok(response.host_info)
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ----- GetFileTransfersCommand -----
@@ -250,10 +667,18 @@ pub struct GetFileTransfersCommand {
 }
 
 impl Command<Vec<FileTransfer>> for GetFileTransfersCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<Vec<FileTransfer>> {
-        let response: Self = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<Vec<FileTransfer>> {
+        let response: Self = deserialize_raw_reply(reply)?;
         Ok(response.file_transfers.file_transfer.unwrap_or_default())
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ----- GetMessagesCommand -----
@@ -263,6 +688,8 @@ struct MessagesDto {
     msg: Option<Vec<Message>>,
 }
 
+/// Gets the messages with a sequence number greater than the given one,
+/// in chronological order, i.e. sorted by sequence number and then by time.
 #[derive(Default, Deserialize, Serialize)]
 #[serde(rename(serialize = "get_messages"))]
 pub struct GetMessagesCommand {
@@ -281,10 +708,26 @@ impl GetMessagesCommand {
     }
 }
 
+impl MessagesDto {
+    fn into_chronological(self) -> Vec<Message> {
+        let mut msgs = self.msg.unwrap_or_default();
+        msgs.sort_by(|a, b| a.seqno.cmp(&b.seqno).then_with(|| a.timestamp.0.total_cmp(&b.timestamp.0)));
+        msgs
+    }
+}
+
 impl Command<Vec<Message>> for GetMessagesCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<Vec<Message>> {
-        let response: Self = execute_rpc_operation(connection, self)?;
-        Ok(response.msgs.msg.unwrap_or_default())
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<Vec<Message>> {
+        let response: Self = deserialize_raw_reply(reply)?;
+        Ok(response.msgs.into_chronological())
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
     }
 }
 
@@ -314,10 +757,56 @@ impl GetNoticesCommand {
 }
 
 impl Command<Vec<Notice>> for GetNoticesCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<Vec<Notice>> {
-        let response: Self = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<Vec<Notice>> {
+        let response: Self = deserialize_raw_reply(reply)?;
         Ok(response.notices.notice.unwrap_or_default())
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+
+    fn required_client_version(&self) -> Option<Version> {
+        Some(NOTICES_CLIENT_VERSION)
+    }
+}
+
+// ----- GetProjectCommand -----
+
+pub struct GetProjectCommand {
+    project_url: String,
+}
+
+impl GetProjectCommand {
+    pub fn new(project_url: String) -> Self {
+        Self { project_url }
+    }
+}
+
+impl Command<Option<Project>> for GetProjectCommand {
+    fn request(&self) -> Result<Vec<u8>> {
+        GetProjectStatusCommand::default().request()
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<Option<Project>> {
+        let projects = GetProjectStatusCommand::default().parse_reply(reply)?;
+        Ok(projects
+            .into_iter()
+            .find(|project| is_same_project_url(&project.master_url, &self.project_url)))
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+}
+
+// users frequently omit the trailing slash of the master url
+fn is_same_project_url(a: &str, b: &str) -> bool {
+    normalize_project_url(a) == normalize_project_url(b)
 }
 
 // ----- GetProjectStatusCommand -----
@@ -335,11 +824,18 @@ pub struct GetProjectStatusCommand {
 }
 
 impl Command<Vec<Project>> for GetProjectStatusCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<Vec<Project>> {
-        let response: Self = execute_preprocessed_rpc_operation(
-            connection, self, |s| s.replace("<ifteam>", "").replace("</ifteam>", ""))?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<Vec<Project>> {
+        let response: Self = deserialize_preprocessed_reply(reply, strip_wrapper_tags(PROJECT_WRAPPER_TAGS))?;
         Ok(response.projects.project.unwrap_or_default())
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ----- GetResultsCommand -----
@@ -368,10 +864,88 @@ impl GetResultsCommand {
 }
 
 impl Command<Vec<Task>> for GetResultsCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<Vec<Task>> {
-        let response: Self = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<Vec<Task>> {
+        let response: Self = deserialize_raw_reply(reply)?;
         Ok(response.results.result.unwrap_or_default())
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+}
+
+// ----- GetStateCommand -----
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct ClientStateDto {
+    host_info: HostInfo,
+    net_stats: NetStats,
+    time_stats: TimeStats,
+    app: Vec<App>,
+    project: Vec<Project>,
+    result: Vec<Task>,
+    platform_name: String,
+    platform: Vec<String>,
+}
+
+impl From<ClientStateDto> for CCState {
+    fn from(state: ClientStateDto) -> Self {
+        // older clients report their primary platform only
+        let platforms = if state.platform.is_empty() && !state.platform_name.is_empty() {
+            vec![state.platform_name]
+        } else {
+            state.platform
+        };
+        CCState {
+            host_info: state.host_info,
+            net_stats: state.net_stats,
+            time_stats: state.time_stats,
+            apps: state.app,
+            projects: state.project,
+            tasks: state.result,
+            platforms,
+        }
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(rename(serialize = "get_state"))]
+pub struct GetStateCommand {
+    #[serde(skip_serializing)]
+    client_state: ClientStateDto,
+}
+
+impl GetStateCommand {
+    /// Parses a saved client_state document, e.g. a dump of a get_state reply.
+    pub fn parse(xml: &str) -> Result<CCState> {
+        let xml = xml.trim();
+        let xml = xml
+            .strip_prefix("<boinc_gui_rpc_reply>")
+            .and_then(|xml| xml.strip_suffix("</boinc_gui_rpc_reply>"))
+            .unwrap_or(xml);
+        let response: Self = deserialize_preprocessed_reply(xml.as_bytes().to_vec(), strip_wrapper_tags(PROJECT_WRAPPER_TAGS))?;
+        Ok(response.client_state.into())
+    }
+}
+
+impl Command<CCState> for GetStateCommand {
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<CCState> {
+        let response: Self = deserialize_preprocessed_reply(reply, strip_wrapper_tags(PROJECT_WRAPPER_TAGS))?;
+        Ok(response.client_state.into())
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ----- NetworkAvailableCommand -----
@@ -381,12 +955,41 @@ impl Command<Vec<Task>> for GetResultsCommand {
 pub struct NetworkAvailableCommand {}
 
 impl Command<()> for NetworkAvailableCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<()> {
-        let _: SuccessReply = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
         Ok(())
     }
 }
 
+// ----- PingCommand -----
+
+/// Cheap request to check the client is responding.
+///
+/// Executing it periodically keeps long-lived connections from being
+/// dropped by NATs or firewalls, as the std TcpStream has no keepalive option.
+#[derive(Default, Serialize)]
+#[serde(rename(serialize = "get_cc_status"))]
+pub struct PingCommand {}
+
+impl Command<()> for PingCommand {
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: IgnoredAny = deserialize_raw_reply(reply)?;
+        Ok(())
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+}
+
 // ----- ProjectOpCommand -----
 
 #[derive(Serialize)]
@@ -419,6 +1022,10 @@ pub struct ProjectOpCommand {
 
 impl ProjectOpCommand {
     pub fn new(project_url: String, op: ProjectOp) -> Self {
+        Self::new_verbatim(normalize_project_url(&project_url), op)
+    }
+
+    pub fn new_verbatim(project_url: String, op: ProjectOp) -> Self {
         Self {
             dto: match op {
                 ProjectOp::Allowmorework => ProjectOpDto::Allowmorework { project_url },
@@ -436,8 +1043,12 @@ impl ProjectOpCommand {
 }
 
 impl Command<()> for ProjectOpCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<()> {
-        let _: SuccessReply = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
         Ok(())
     }
 }
@@ -449,8 +1060,12 @@ impl Command<()> for ProjectOpCommand {
 pub struct ReadCCConfigCommand {}
 
 impl Command<()> for ReadCCConfigCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<()> {
-        let _: SuccessReply = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
         Ok(())
     }
 }
@@ -462,8 +1077,12 @@ impl Command<()> for ReadCCConfigCommand {
 pub struct ReadGlobalPreferencesOverrideCommand {}
 
 impl Command<()> for ReadGlobalPreferencesOverrideCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<()> {
-        let _: SuccessReply = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
         Ok(())
     }
 }
@@ -475,8 +1094,12 @@ impl Command<()> for ReadGlobalPreferencesOverrideCommand {
 pub struct RunBenchmarksCommand {}
 
 impl Command<()> for RunBenchmarksCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<()> {
-        let _: SuccessReply = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
         Ok(())
     }
 }
@@ -488,10 +1111,67 @@ impl Command<()> for RunBenchmarksCommand {
 pub struct QuitCommand {}
 
 impl Command<()> for QuitCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<()> {
-        let _: SuccessReply = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
+        Ok(())
+    }
+}
+
+// ----- SetCcConfigCommand -----
+
+#[derive(Serialize)]
+#[serde(rename = "cc_config")]
+struct CcConfigDto {
+    options: CcConfig,
+}
+
+#[derive(Serialize)]
+#[serde(rename(serialize = "set_cc_config"))]
+pub struct SetCcConfigCommand {
+    cc_config: CcConfigDto,
+}
+
+impl SetCcConfigCommand {
+    /// Replaces the options of the client's cc_config.xml, leaving the log flags to the client's defaults.
+    pub fn new(cc_config: CcConfig) -> Self {
+        Self { cc_config: CcConfigDto { options: cc_config } }
+    }
+
+    // the options plus a generous estimate per GPU exclusion, pre-allocated for serializing the request
+    fn request_size_hint(&self) -> usize {
+        512 + 192 * self.cc_config.options.exclude_gpu.len()
+    }
+}
+
+impl Command<()> for SetCcConfigCommand {
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec_with_capacity(self.request_size_hint(), self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
         Ok(())
     }
+
+    fn required_client_version(&self) -> Option<Version> {
+        Some(CC_CONFIG_CLIENT_VERSION)
+    }
+}
+
+// ----- Set*ModeCommand -----
+
+// The duration of a mode in seconds, zero sets it permanently.
+// The client would misinterpret anything else, so it isn't sent at all.
+fn validate_mode_duration(duration: f64) -> Result<()> {
+    if !(duration.is_finite() && duration >= 0.) {
+        return Err(Error::InvalidArgument(format!(
+            "the duration of a mode must be a finite number of seconds >= 0, got {}", duration)));
+    }
+    Ok(())
 }
 
 // ----- SetGpuModeCommand -----
@@ -504,15 +1184,20 @@ pub struct SetGpuModeCommand {
 }
 
 impl SetGpuModeCommand {
-    pub fn new(mode: RunMode, duration: f64) -> Self {
-        assert!(mode != RunMode::UnknownToJoinc);
-        Self { mode, duration }
+    /// Fails with `Error::InvalidArgument` for a negative or not finite duration.
+    pub fn new(mode: SettableRunMode, duration: f64) -> Result<Self> {
+        validate_mode_duration(duration)?;
+        Ok(Self { mode: mode.into(), duration })
     }
 }
 
 impl Command<()> for SetGpuModeCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<()> {
-        let _: SuccessReply = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
         Ok(())
     }
 }
@@ -527,19 +1212,54 @@ pub struct SetNetworkModeCommand {
 }
 
 impl SetNetworkModeCommand {
-    pub fn new(mode: RunMode, duration: f64) -> Self {
-        assert!(mode != RunMode::UnknownToJoinc);
-        Self { mode, duration }
+    /// Fails with `Error::InvalidArgument` for a negative or not finite duration.
+    pub fn new(mode: SettableRunMode, duration: f64) -> Result<Self> {
+        validate_mode_duration(duration)?;
+        Ok(Self { mode: mode.into(), duration })
     }
 }
 
 impl Command<()> for SetNetworkModeCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<()> {
-        let _: SuccessReply = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
         Ok(())
     }
 }
 
+// ----- SetRawCcConfigCommand -----
+
+/// Replaces the client's cc_config.xml with the document verbatim,
+/// e.g. one of [GetCcConfigCommand] with a changed log flag.
+pub struct SetRawCcConfigCommand {
+    cc_config: RawCcConfig,
+}
+
+impl SetRawCcConfigCommand {
+    pub fn new(cc_config: RawCcConfig) -> Self {
+        Self { cc_config }
+    }
+}
+
+impl Command<()> for SetRawCcConfigCommand {
+    // the document is already XML, so it's embedded as is instead of being serialized
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(format!("<set_cc_config>{}</set_cc_config>", self.cc_config.as_str()).into_bytes())
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
+        Ok(())
+    }
+
+    fn required_client_version(&self) -> Option<Version> {
+        Some(CC_CONFIG_CLIENT_VERSION)
+    }
+}
+
 // ----- SetRunModeCommand -----
 
 #[derive(Default, Serialize)]
@@ -550,15 +1270,20 @@ pub struct SetRunModeCommand {
 }
 
 impl SetRunModeCommand {
-    pub fn new(mode: RunMode, duration: f64) -> Self {
-        assert!(mode != RunMode::UnknownToJoinc);
-        Self { mode, duration }
+    /// Fails with `Error::InvalidArgument` for a negative or not finite duration.
+    pub fn new(mode: SettableRunMode, duration: f64) -> Result<Self> {
+        validate_mode_duration(duration)?;
+        Ok(Self { mode: mode.into(), duration })
     }
 }
 
 impl Command<()> for SetRunModeCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<()> {
-        let _: SuccessReply = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
         Ok(())
     }
 }
@@ -583,6 +1308,10 @@ pub struct TaskOpCommand {
 
 impl TaskOpCommand {
     pub fn new(project_url: String, name: String, op: TaskOp) -> Self {
+        Self::new_verbatim(normalize_project_url(&project_url), name, op)
+    }
+
+    pub fn new_verbatim(project_url: String, name: String, op: TaskOp) -> Self {
         Self {
             dto: match op {
                 TaskOp::Abort => TaskOpDto::Abort { project_url, name },
@@ -594,8 +1323,12 @@ impl TaskOpCommand {
 }
 
 impl Command<()> for TaskOpCommand {
-    fn execute(&mut self, connection: &mut Connection) -> Result<()> {
-        let _: SuccessReply = execute_rpc_operation(connection, self)?;
+    fn request(&self) -> Result<Vec<u8>> {
+        Ok(to_vec(self)?)
+    }
+
+    fn parse_reply(&self, reply: Vec<u8>) -> Result<()> {
+        let _: SuccessReply = deserialize_raw_reply(reply)?;
         Ok(())
     }
 }
@@ -605,11 +1338,496 @@ impl Command<()> for TaskOpCommand {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rpc::connection::Connection;
+    use crate::rpc::testing::{serve_replies, FakeClock, ScriptedTransport};
+    use std::cell::RefCell;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn times_out_on_hanging_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (_stream, _) = listener.accept().unwrap();
+            thread::sleep(std::time::Duration::from_secs(2));
+        });
+        let mut connection = Connection::open("127.0.0.1", port).unwrap();
+
+        let started = std::time::Instant::now();
+        let result = GetCCStatusCommand::default().execute_within(&mut connection, std::time::Duration::from_millis(50));
+        assert!(matches!(result, Err(Error::Rpc(msg)) if msg == "command timed out"));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn times_out_on_trickling_client() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut byte = [0u8];
+            while stream.read(&mut byte).unwrap() == 1 && byte[0] != 0x03 {}
+            // each byte well within the timeout, the reply by far not
+            for byte in b"<boinc_gui_rpc_reply>\n<cc_status/>\n</boinc_gui_rpc_reply>\n\x03" {
+                thread::sleep(std::time::Duration::from_millis(30));
+                if stream.write_all(&[*byte]).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut connection = Connection::open("127.0.0.1", port).unwrap();
+
+        let started = std::time::Instant::now();
+        let result = GetCCStatusCommand::default().execute_within(&mut connection, std::time::Duration::from_millis(200));
+        assert!(matches!(result, Err(Error::Rpc(msg)) if msg == "command timed out"));
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+
+        // the rest of the reply is still on its way
+        let result = GetCCStatusCommand::default().execute(&mut connection);
+        assert!(matches!(result, Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::NotConnected));
+    }
+
+    #[test]
+    fn executes_within_timeout() {
+        let mut connection = Connection::open("127.0.0.1", serve_replies(["<cc_status/>", "<cc_status/>"])).unwrap();
+        connection.set_timeout(Some(std::time::Duration::from_secs(7))).unwrap();
+        assert!(GetCCStatusCommand::default().execute_within(&mut connection, std::time::Duration::from_secs(5)).is_ok());
+        assert_eq!(connection.timeout(), Some(std::time::Duration::from_secs(7)));
+        assert!(GetCCStatusCommand::default().execute(&mut connection).is_ok());
+    }
+
+    fn fast_retry_policy(max_attempts: u32, retry_mutating: bool) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: std::time::Duration::from_millis(1),
+            retry_mutating,
+        }
+    }
+
+    fn poll_options(timeout_secs: u64, interval_secs: u64) -> PollOptions {
+        PollOptions {
+            overall_timeout: Some(std::time::Duration::from_secs(timeout_secs)),
+            interval: std::time::Duration::from_secs(interval_secs),
+        }
+    }
+
+    // Hands out the transports in turn, as if each was a newly opened connection.
+    fn connector<const N: usize>(transports: [ScriptedTransport; N]) -> impl FnMut() -> Result<ScriptedTransport> {
+        let mut transports = transports.into_iter();
+        move || Ok(transports.next().expect("no further connection scripted"))
+    }
+
+    fn server_version(major: i32) -> String {
+        format!("<server_version><major>{}</major><minor>0</minor><release>0</release></server_version>", major)
+    }
+
+    fn timed_out() -> Error {
+        Error::Io(std::io::ErrorKind::TimedOut.into())
+    }
+
+    #[test]
+    fn retries_until_success() {
+        let transports = [
+            ScriptedTransport::new().fail(timed_out()),
+            ScriptedTransport::new().fail(timed_out()),
+            ScriptedTransport::replying([server_version(3).as_str()]),
+        ];
+        let result = execute_with_retry(&mut ExchangeVersionsCommand::default(), connector(transports.clone()), None, &fast_retry_policy(3, false));
+        assert_eq!(result.unwrap(), Version { major: 3, minor: 0, release: 0 });
+        assert!(transports.iter().all(|transport| transport.request_tags() == ["exchange_versions"]));
+    }
+
+    #[test]
+    fn retries_on_new_connection_ignoring_stale_reply() {
+        let transports = [
+            // the reply of the timed out exchange arrives after all
+            ScriptedTransport::new().authorizing().fail(timed_out()).reply(&server_version(1)),
+            ScriptedTransport::new().authorizing().reply(&server_version(2)),
+        ];
+        let result = execute_with_retry(&mut ExchangeVersionsCommand::default(), connector(transports.clone()), Some("secret"), &fast_retry_policy(3, false));
+
+        // retrying on the first connection would read the stale reply of major 1
+        assert_eq!(result.unwrap(), Version { major: 2, minor: 0, release: 0 });
+        for transport in &transports {
+            assert_eq!(transport.request_tags(), ["auth1", "auth2", "exchange_versions"]);
+        }
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let transports = [ScriptedTransport::new().fail(timed_out()), ScriptedTransport::new().fail(timed_out())];
+        let result = execute_with_retry(&mut ExchangeVersionsCommand::default(), connector(transports.clone()), None, &fast_retry_policy(2, false));
+        assert!(matches!(result, Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::TimedOut));
+    }
+
+    #[test]
+    fn doesnt_retry_mutating_commands_by_default() {
+        let transports = [ScriptedTransport::new().fail(timed_out()), ScriptedTransport::replying(["<success/>"])];
+        let result = execute_with_retry(&mut NetworkAvailableCommand::default(), connector(transports.clone()), None, &fast_retry_policy(3, false));
+        assert!(matches!(result, Err(Error::Io(_))));
+        assert!(transports[1].requests().is_empty());
+    }
+
+    #[test]
+    fn retries_mutating_commands_if_allowed() {
+        let transports = [ScriptedTransport::new().fail(timed_out()), ScriptedTransport::replying(["<success/>"])];
+        let result = execute_with_retry(&mut NetworkAvailableCommand::default(), connector(transports.clone()), None, &fast_retry_policy(3, true));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn doesnt_retry_permanent_errors() {
+        for err in [Error::Rpc("port 80 does not appear to be a BOINC GUI RPC endpoint".to_string()), Error::Client("nope".to_string())] {
+            let transports = [ScriptedTransport::new().fail(err), ScriptedTransport::replying([server_version(7).as_str()])];
+            let result = execute_with_retry(&mut ExchangeVersionsCommand::default(), connector(transports.clone()), None, &fast_retry_policy(3, false));
+            assert!(matches!(result, Err(Error::Rpc(_) | Error::Client(_))));
+            assert!(transports[1].requests().is_empty());
+        }
+    }
+
+    #[test]
+    fn gets_state_with_interleaved_lists() {
+        let mut connection = ScriptedTransport::replying([concat!(
+            "<client_state>",
+            "<host_info><p_ncpus>4</p_ncpus></host_info>",
+            "<net_stats><bwup>12.5</bwup></net_stats>",
+            "<time_stats><on_frac>0.9</on_frac></time_stats>",
+            "<project><master_url>https://a.org/</master_url></project>",
+            "<app><name>a_app</name></app>",
+            "<workunit><name>wu</name></workunit>",
+            "<result><name>a_task</name></result>",
+            "<project><master_url>https://b.org/</master_url></project>",
+            "<app><name>b_app</name></app>",
+            "<result><name>b_task</name></result>",
+            "<platform_name>x86_64-pc-linux-gnu</platform_name>",
+            "</client_state>")]);
+
+        let state = GetStateCommand::default().execute(&mut connection).unwrap();
+
+        assert_eq!(state.host_info.p_ncpus, 4);
+        assert_eq!(state.net_stats.bwup, 12.5);
+        assert_eq!(state.time_stats.on_frac, 0.9);
+        assert_eq!(state.projects.iter().map(|p| p.master_url.as_str()).collect::<Vec<_>>(), ["https://a.org/", "https://b.org/"]);
+        assert_eq!(state.apps.iter().map(|a| a.name.as_str()).collect::<Vec<_>>(), ["a_app", "b_app"]);
+        assert_eq!(state.tasks.iter().map(|t| t.name.as_str()).collect::<Vec<_>>(), ["a_task", "b_task"]);
+    }
+
+    #[test]
+    fn fetches_raw_reply() {
+        let mut connection = ScriptedTransport::replying(["<client_state><x>1</x></client_state>", "<unauthorized/>"]);
+        let reply = fetch_raw_reply(&mut connection, &GetStateCommand::default()).unwrap();
+        assert_eq!(reply, "<client_state><x>1</x></client_state>");
+        assert!(matches!(fetch_raw_reply(&mut connection, &GetStateCommand::default()), Err(Error::Unauthorized)));
+    }
+
+    #[test]
+    fn parses_saved_state() {
+        let state = GetStateCommand::parse(
+            "<client_state><project><master_url>https://a.org/</master_url></project></client_state>").unwrap();
+        assert_eq!(state.projects[0].master_url, "https://a.org/");
+
+        let state = GetStateCommand::parse(
+            "<boinc_gui_rpc_reply>\n<client_state><result><name>t</name></result></client_state>\n</boinc_gui_rpc_reply>\n").unwrap();
+        assert_eq!(state.tasks[0].name, "t");
+
+        assert!(GetStateCommand::parse("<unauthorized/>").is_err());
+    }
+
+    #[test]
+    fn gets_all_projects_list() {
+        let mut connection = ScriptedTransport::replying([concat!(
+            "<projects>",
+            "<project><name>Einstein@Home</name><url>https://einsteinathome.org/</url>",
+            "<platforms><name>windows_x86_64</name><name>x86_64-pc-linux-gnu[opencl]</name></platforms>",
+            "</project>",
+            "<project><name>Unknown</name><url>https://unknown.org/</url></project>",
+            "<account_manager><name>BAM!</name><url>https://boincstats.com/</url></account_manager>",
+            "</projects>")]);
+        let projects = GetAllProjectsListCommand::default().execute(&mut connection).unwrap();
+
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].name, "Einstein@Home");
+        assert_eq!(projects[0].platforms.0, ["windows_x86_64", "x86_64-pc-linux-gnu[opencl]"]);
+        assert!(projects[1].platforms.0.is_empty());
+    }
+
+    #[test]
+    fn gets_platforms_of_state() {
+        let mut connection = ScriptedTransport::replying([concat!(
+            "<client_state>",
+            "<platform_name>x86_64-pc-linux-gnu</platform_name>",
+            "<platform>x86_64-pc-linux-gnu</platform>",
+            "<platform>i686-pc-linux-gnu</platform>",
+            "</client_state>")]);
+        let state = GetStateCommand::default().execute(&mut connection).unwrap();
+        assert_eq!(state.platforms, ["x86_64-pc-linux-gnu", "i686-pc-linux-gnu"]);
+
+        let mut connection = ScriptedTransport::replying([
+            "<client_state><platform_name>x86_64-apple-darwin</platform_name></client_state>"]);
+        let state = GetStateCommand::default().execute(&mut connection).unwrap();
+        assert_eq!(state.platforms, ["x86_64-apple-darwin"]);
+    }
+
+    #[test]
+    fn gets_app_config_of_projects() {
+        let mut connection = ScriptedTransport::replying([concat!(
+            "<client_state>",
+            "<project><master_url>https://a.org/</master_url>",
+            "<app_config>",
+            "<app><name>sim</name><max_concurrent>2</max_concurrent>",
+            "<gpu_versions><gpu_usage>0.5</gpu_usage><cpu_usage>0.4</cpu_usage></gpu_versions></app>",
+            "<app><name>analysis</name><max_concurrent>1</max_concurrent></app>",
+            "<app_version><app_name>sim</app_name><plan_class>mt</plan_class><avg_ncpus>4</avg_ncpus></app_version>",
+            "<project_max_concurrent>3</project_max_concurrent>",
+            "<report_results_immediately/>",
+            "</app_config>",
+            "</project>",
+            "<project><master_url>https://b.org/</master_url></project>",
+            "</client_state>")]);
+        let state = GetStateCommand::default().execute(&mut connection).unwrap();
+
+        let app_config = &state.projects[0].app_config;
+        assert_eq!(app_config.project_max_concurrent, 3);
+        assert_eq!(app_config.report_results_immediately, Bool::True);
+        assert_eq!(app_config.app.len(), 2);
+        assert_eq!(app_config.app[0].name, "sim");
+        assert_eq!(app_config.app[0].max_concurrent, 2);
+        let gpu_versions = app_config.app[0].gpu_versions.as_ref().unwrap();
+        assert_eq!((gpu_versions.cpu_usage, gpu_versions.gpu_usage), (0.4, 0.5));
+        assert!(app_config.app[1].gpu_versions.is_none());
+        assert_eq!(app_config.app_version[0].plan_class, "mt");
+        assert_eq!(app_config.app_version[0].avg_ncpus, 4.);
+
+        let app_config = &state.projects[1].app_config;
+        assert_eq!(app_config.project_max_concurrent, 0);
+        assert!(app_config.app.is_empty() && app_config.app_version.is_empty());
+    }
+
+    #[test]
+    fn wraps_reply_in_root_in_place() {
+        let mut reply = String::with_capacity(64);
+        reply.push_str("<success/>");
+        let buffer = reply.as_ptr();
+
+        let wrapped = wrap_in_root(reply);
+        assert_eq!(wrapped, "<root><success/></root>");
+        assert_eq!(wrapped.as_ptr(), buffer);
+    }
+
+    #[test]
+    fn strips_wrapper_tags() {
+        let strip = strip_wrapper_tags(&["ifteam", "ifclass"]);
+        assert_eq!(
+            strip("<project><ifteam><team_name>t</team_name></ifteam><ifclass/></project>".to_string()),
+            "<project><team_name>t</team_name></project>");
+        assert_eq!(
+            strip("<a><ifclass><b>1</b></ifclass><ifteam><c>2</c></ifteam><ifclass><b>3</b></ifclass></a>".to_string()),
+            "<a><b>1</b><c>2</c><b>3</b></a>");
+        assert_eq!(strip("<team_name>ifteam</team_name>".to_string()), "<team_name>ifteam</team_name>");
+    }
+
+    #[test]
+    fn accepts_valid_mode_durations() {
+        assert!(SetRunModeCommand::new(SettableRunMode::Never, 0.).is_ok());
+        assert!(SetGpuModeCommand::new(SettableRunMode::Never, 3600.).is_ok());
+        assert!(SetNetworkModeCommand::new(SettableRunMode::Auto, 0.5).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_mode_durations() {
+        let result = SetRunModeCommand::new(SettableRunMode::Never, -1.);
+        assert!(matches!(result, Err(Error::InvalidArgument(msg)) if msg.ends_with("got -1")));
+        let result = SetGpuModeCommand::new(SettableRunMode::Never, f64::NAN);
+        assert!(matches!(result, Err(Error::InvalidArgument(msg)) if msg.ends_with("got NaN")));
+        let result = SetNetworkModeCommand::new(SettableRunMode::Never, f64::INFINITY);
+        assert!(matches!(result, Err(Error::InvalidArgument(msg)) if msg.ends_with("got inf")));
+    }
+
+    #[test]
+    fn serializes_set_mode_commands() {
+        let serialized = to_vec(&SetRunModeCommand::new(SettableRunMode::Restore, 0.).unwrap()).unwrap();
+        assert_eq!(String::from_utf8(serialized).unwrap(), "<set_run_mode><restore/><duration>0</duration></set_run_mode>");
+    }
+
+    #[test]
+    fn gets_messages_in_chronological_order() {
+        let mut connection = ScriptedTransport::replying([concat!(
+            "<msgs>",
+            "<msg><seqno>3</seqno><time>300</time></msg>",
+            "<msg><seqno>1</seqno><time>100</time></msg>",
+            "<msg><seqno>2</seqno><time>250</time></msg>",
+            "<msg><seqno>2</seqno><time>200</time></msg>",
+            "</msgs>")]);
+        let msgs = GetMessagesCommand::new(0).execute(&mut connection).unwrap();
+
+        let order: Vec<(i32, f64)> = msgs.iter().map(|msg| (msg.seqno, msg.timestamp.0)).collect();
+        assert_eq!(order, [(1, 100.), (2, 200.), (2, 250.), (3, 300.)]);
+    }
+
+    #[test]
+    fn polls_until_result() {
+        let clock = FakeClock::default();
+        let mut polls = 0;
+
+        let result = poll_until(&poll_options(10, 1), &clock, || {
+            polls += 1;
+            Ok(Some(polls).filter(|&n| n == 3))
+        });
+
+        assert_eq!(result.unwrap(), Some(3));
+    }
+
+    #[test]
+    fn polls_without_deadline_until_result() {
+        let clock = FakeClock::default();
+        let options = PollOptions { overall_timeout: None, interval: std::time::Duration::from_secs(3600) };
+        let mut polls = 0;
+
+        let result = poll_until(&options, &clock, || {
+            polls += 1;
+            Ok(Some(polls).filter(|&n| n == 1000))
+        });
+
+        assert_eq!(result.unwrap(), Some(1000));
+    }
+
+    #[test]
+    fn gives_up_polling_at_deadline() {
+        let start = std::time::Instant::now();
+        let clock = FakeClock::new(start);
+        let mut polls = 0;
+
+        let result: Result<Option<()>> = poll_until(&poll_options(10, 3), &clock, || {
+            polls += 1;
+            Ok(None)
+        });
+
+        assert_eq!(result.unwrap(), None);
+        assert_eq!(polls, 4);
+        assert!(clock.now() - start <= std::time::Duration::from_secs(10));
+    }
+
+    #[test]
+    fn stops_polling_on_error() {
+        let clock = FakeClock::default();
+        let result: Result<Option<()>> = poll_until(&poll_options(10, 1), &clock, || Err(Error::Client("nope".to_string())));
+        assert!(matches!(result, Err(Error::Client(_))));
+    }
+
+    #[test]
+    fn pings_client() {
+        let mut connection = ScriptedTransport::replying(["<cc_status><network_status>0</network_status></cc_status>"]);
+        assert!(PingCommand::default().execute(&mut connection).is_ok());
+    }
+
+    #[test]
+    fn fails_to_ping_unauthorized() {
+        let mut connection = ScriptedTransport::replying(["<unauthorized/>"]);
+        assert!(matches!(PingCommand::default().execute(&mut connection), Err(Error::Unauthorized)));
+    }
+
+    #[test]
+    fn decodes_replies() {
+        assert_eq!(decode_reply(b"\xef\xbb\xbf<success/>".to_vec()).unwrap(), "<success/>");
+        assert_eq!(decode_reply(b"<success/>".to_vec()).unwrap(), "<success/>");
+        assert!(matches!(decode_reply(b"<a>\xff</a>".to_vec()),
+            Err(Error::Rpc(msg)) if msg == "Received a non-UTF-8 reply from the client, invalid byte at offset 3"));
+    }
+
+    #[test]
+    fn deserializes_expected_reply() {
+        let reply: Result<SuccessReply> = deserialize_reply("<root><success/></root>");
+        assert!(reply.is_ok());
+    }
+
+    #[test]
+    fn deserializes_error_reply() {
+        let reply: Result<SuccessReply> = deserialize_reply("<root><error>Missing URL</error></root>");
+        assert!(matches!(reply, Err(Error::Client(msg)) if msg == "Missing URL"));
+    }
+
+    #[test]
+    fn deserializes_rejected_set_run_mode_reply() {
+        let reply: Result<SuccessReply> =
+            deserialize_reply("<root><error>Benchmarks running<error_num>-108</error_num></error></root>");
+        assert!(matches!(reply, Err(Error::Rejected(msg, -108)) if msg == "Benchmarks running"));
+
+        let reply: Result<SuccessReply> =
+            deserialize_reply("<root><error>Benchmarks running</error><error_num>-108</error_num></root>");
+        assert!(matches!(reply, Err(Error::Rejected(msg, -108)) if msg == "Benchmarks running"));
+    }
+
+    #[test]
+    fn deserializes_unauthorized_reply() {
+        let reply: Result<SuccessReply> = deserialize_reply("<root><unauthorized/></root>");
+        assert!(matches!(reply, Err(Error::Unauthorized)));
+    }
+
+    #[test]
+    fn fails_on_unexpected_reply() {
+        let reply: Result<SuccessReply> = deserialize_reply("<root><foo>bar</foo></root>");
+        assert!(matches!(reply, Err(Error::Deserialization(_))));
+    }
+
+    #[test]
+    fn matches_project_urls_ignoring_trailing_slash() {
+        assert!(is_same_project_url("http://p.org/", "http://p.org/"));
+        assert!(is_same_project_url("http://p.org/", "http://p.org"));
+        assert!(is_same_project_url("http://p.org", "http://p.org/"));
+        assert!(!is_same_project_url("http://p.org/", "http://q.org/"));
+        assert!(!is_same_project_url("http://p.org/a/", "http://p.org/"));
+    }
+
+    #[test]
+    fn normalizes_project_urls() {
+        assert_eq!(normalize_project_url("http://p.org"), "http://p.org/");
+        assert_eq!(normalize_project_url("http://p.org/"), "http://p.org/");
+        assert_eq!(normalize_project_url("HTTPS://P.Org/Path"), "https://p.org/Path/");
+        assert_eq!(normalize_project_url("p.org"), "p.org/");
+    }
+
+    #[test]
+    fn serializes_auth2_operation_with_hash() {
+        let subject = Auth2Operation::with_hash("0123456789abcdef".to_string());
+        let expected = "<auth2><nonce_hash>0123456789abcdef</nonce_hash></auth2>";
+        assert_eq!(
+            String::from_utf8(super::to_vec(&subject).unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn hashes_nonce_and_password_with_md5() {
+        let subject = AuthorizeCommand::new("secret");
+        let expected = format!("{:x}", md5::compute("noncesecret"));
+        assert_eq!(subject.auth2_operation("nonce").nonce_hash, expected);
+    }
+
+    #[test]
+    fn hashes_nonce_and_password_with_given_hasher() {
+        struct FakeHasher {
+            calls: RefCell<Vec<(String, String)>>,
+        }
+
+        impl NonceHasher for FakeHasher {
+            fn hash(&self, nonce: &str, password: &str) -> String {
+                self.calls.borrow_mut().push((nonce.to_string(), password.to_string()));
+                "fake".to_string()
+            }
+        }
+
+        let subject = AuthorizeCommand::with_hasher("secret", FakeHasher { calls: RefCell::new(vec![]) });
+
+        assert_eq!(subject.auth2_operation("nonce").nonce_hash, "fake");
+        assert_eq!(*subject.hasher.calls.borrow(), vec![("nonce".to_string(), "secret".to_string())]);
+    }
 
     #[test]
     fn serializes_file_transfer_op_command() {
         let subject = FileTransferOpCommand::new("foo.bar".to_string(), "Some file transfer".to_string(), FileTransferOp::Retry);
-        let expected = "<retry_file_transfer><project_url>foo.bar</project_url><filename>Some file transfer</filename></retry_file_transfer>";
+        let expected = "<retry_file_transfer><project_url>foo.bar/</project_url><filename>Some file transfer</filename></retry_file_transfer>";
         assert_eq!(
             String::from_utf8(super::to_vec(&subject).unwrap()).unwrap(),
             expected
@@ -619,21 +1837,92 @@ mod tests {
     #[test]
     fn serializes_project_op_command() {
         let subject = ProjectOpCommand::new("foo.bar".to_string(), ProjectOp::Resume);
-        let expected = "<project_resume><project_url>foo.bar</project_url></project_resume>";
+        let expected = "<project_resume><project_url>foo.bar/</project_url></project_resume>";
         assert_eq!(
             String::from_utf8(super::to_vec(&subject).unwrap()).unwrap(),
             expected
         );
     }
 
+    #[test]
+    fn serializes_set_cc_config_command() {
+        let subject = SetCcConfigCommand::new(CcConfig {
+            report_results_immediately: Bool::True,
+            max_file_xfers: Some(4),
+            ncpus: Some(2),
+            exclude_gpu: vec![
+                ExcludeGpu { url: "https://a.org/".to_string(), device_num: Some(1), ..Default::default() },
+                ExcludeGpu { url: "https://b.org/".to_string(), gpu_type: Some("NVIDIA".to_string()), ..Default::default() },
+            ],
+            ..Default::default()
+        });
+        let expected = concat!(
+            "<set_cc_config><cc_config><options>",
+            "<report_results_immediately>1</report_results_immediately>",
+            "<max_file_xfers>4</max_file_xfers><ncpus>2</ncpus>",
+            "<exclude_gpu><url>https://a.org/</url><device_num>1</device_num></exclude_gpu>",
+            "<exclude_gpu><url>https://b.org/</url><type>NVIDIA</type></exclude_gpu>",
+            "</options></cc_config></set_cc_config>");
+        assert_eq!(
+            String::from_utf8(super::to_vec(&subject).unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn gets_cc_config() {
+        let mut connection = ScriptedTransport::replying([
+            "<cc_config><log_flags><task>0</task><cpu_sched>1</cpu_sched></log_flags></cc_config>"]);
+        let cc_config = GetCcConfigCommand::default().execute(&mut connection).unwrap();
+        assert_eq!(cc_config.log_flags.enabled(), ["file_xfer", "sched_ops", "cpu_sched"]);
+    }
+
+    #[test]
+    fn refuses_commands_unknown_to_older_clients() {
+        let mut connection = ScriptedTransport::replying([
+            "<cc_config><log_flags><task>1</task></log_flags></cc_config>"]);
+        // the old client isn't even asked, so the reply is left for the new one
+        assert!(matches!(GetCcConfigCommand::default().execute_for(&mut connection, &Version::new(7, 0, 64)),
+            Err(Error::Client(msg)) if msg == "requires client >= 7.2.0"));
+        assert!(GetCcConfigCommand::default().execute_for(&mut connection, &Version::new(7, 24, 1)).is_ok());
+
+        assert!(GetNoticesCommand::new(0).check_client_version(&Version::new(6, 10, 58)).is_err());
+        assert!(PingCommand::default().check_client_version(&Version::new(5, 10, 45)).is_ok());
+    }
+
+    #[test]
+    fn sets_raw_cc_config_verbatim() {
+        let xml = "<cc_config><options><proxy_info><socks_server_name>a</socks_server_name></proxy_info></options></cc_config>";
+        let subject = SetRawCcConfigCommand::new(RawCcConfig::new(xml.to_string()).unwrap());
+        assert_eq!(String::from_utf8(subject.request().unwrap()).unwrap(), format!("<set_cc_config>{xml}</set_cc_config>"));
+
+        let mut connection = ScriptedTransport::replying(["<success/>"]);
+        assert!(SetRawCcConfigCommand::new(RawCcConfig::new(xml.to_string()).unwrap()).execute(&mut connection).is_ok());
+    }
+
+    #[test]
+    fn sets_cc_config() {
+        let mut connection = ScriptedTransport::replying(["<success/>"]);
+        assert!(SetCcConfigCommand::new(CcConfig::default()).execute(&mut connection).is_ok());
+    }
+
     #[test]
     fn serializes_task_op_command() {
         let subject = TaskOpCommand::new("foo.bar".to_string(), "Some task".to_string(), TaskOp::Abort);
-        let expected = "<abort_result><project_url>foo.bar</project_url><name>Some task</name></abort_result>";
+        let expected = "<abort_result><project_url>foo.bar/</project_url><name>Some task</name></abort_result>";
         assert_eq!(
             String::from_utf8(super::to_vec(&subject).unwrap()).unwrap(),
             expected
         );
     }
 
+    #[test]
+    fn serializes_verbatim_project_urls() {
+        let subject = ProjectOpCommand::new_verbatim("foo.bar".to_string(), ProjectOp::Resume);
+        let expected = "<project_resume><project_url>foo.bar</project_url></project_resume>";
+        assert_eq!(
+            String::from_utf8(super::to_vec(&subject).unwrap()).unwrap(),
+            expected
+        );
+    }
 }
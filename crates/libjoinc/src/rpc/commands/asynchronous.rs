@@ -0,0 +1,120 @@
+//! Async execution of the commands over an [AsyncConnection], mirroring [Command].
+
+use super::*;
+use crate::rpc::connection::AsyncConnection;
+use std::future::Future;
+use std::time::Duration;
+
+/// The commands share their requests and the parsing of the replies with [Command],
+/// so only the transport differs.
+pub trait AsyncCommand<RESP>: Command<RESP> + Send {
+    fn execute_async(&mut self, connection: &mut AsyncConnection) -> impl Future<Output = Result<RESP>> + Send {
+        async move {
+            let request = self.request()?;
+            let reply = connection.do_rpc(&request).await?;
+            self.parse_reply(reply)
+        }
+    }
+
+    /// Like [Command::execute_for], executes the command if the client of the given version knows it.
+    fn execute_for_async(&mut self, connection: &mut AsyncConnection, client_version: &Version) -> impl Future<Output = Result<RESP>> + Send {
+        let checked = self.check_client_version(client_version);
+        async move {
+            checked?;
+            self.execute_async(connection).await
+        }
+    }
+
+    /// Like [Command::execute_within], fails with `Error::Rpc("command timed out")` if it takes longer
+    /// than the timeout in total. The connection is out of sync after a timeout, so it fails any further exchange.
+    fn execute_within_async(&mut self, connection: &mut AsyncConnection, timeout: Duration) -> impl Future<Output = Result<RESP>> + Send {
+        async move {
+            match tokio::time::timeout(timeout, self.execute_async(connection)).await {
+                Ok(result) => result,
+                Err(_) => Err(Error::Rpc("command timed out".to_string())),
+            }
+        }
+    }
+}
+
+impl<C, RESP> AsyncCommand<RESP> for C where C: Command<RESP> + Send {}
+
+impl<H> AuthorizeCommand<H>
+where
+    H: NonceHasher,
+{
+    /// Like [AuthorizeCommand::execute], authorizes the connection in the exchanges of both steps.
+    pub async fn execute_async(&mut self, connection: &mut AsyncConnection) -> Result<()> {
+        let mut auth1 = Auth1Operation::default();
+        let nonce = auth1.execute_async(connection).await?;
+
+        let mut auth2 = self.auth2_operation(&nonce);
+        if auth2.execute_async(connection).await? {
+            Ok(())
+        } else {
+            Err(Error::Unauthorized)
+        }
+    }
+}
+
+// ----- Tests -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::testing::serve_replies;
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    // Opens a connection to a fake client answering each request with the next given reply.
+    async fn mock_client(replies: Vec<&'static str>) -> AsyncConnection {
+        AsyncConnection::open("127.0.0.1", serve_replies(replies)).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn executes_command() {
+        let mut connection = mock_client(vec!["<cc_status><network_status>1</network_status></cc_status>"]).await;
+        let status = GetCCStatusCommand::default().execute_async(&mut connection).await.unwrap();
+        assert_eq!(status.network_status, NetworkStatus::WantConnection);
+    }
+
+    #[tokio::test]
+    async fn authorizes_in_two_round_trips() {
+        let mut connection = mock_client(vec!["<nonce>123</nonce>", "<authorized/>"]).await;
+        assert!(AuthorizeCommand::new("secret").execute_async(&mut connection).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn refuses_command_unknown_to_older_client() {
+        let mut connection = mock_client(vec![]).await;
+        let result = GetNoticesCommand::new(0).execute_for_async(&mut connection, &Version::new(6, 10, 58)).await;
+        assert!(matches!(result, Err(Error::Client(msg)) if msg == "requires client >= 6.11.0"));
+    }
+
+    #[tokio::test]
+    async fn times_out_and_refuses_further_exchanges() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        // reads the requests but never replies
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buffer = [0u8; 256];
+            while stream.read(&mut buffer).await.unwrap() > 0 {}
+        });
+
+        let mut connection = AsyncConnection::open("127.0.0.1", port).await.unwrap();
+        let result = GetCCStatusCommand::default().execute_within_async(&mut connection, Duration::from_millis(50)).await;
+        assert!(matches!(result, Err(Error::Rpc(msg)) if msg == "command timed out"));
+
+        let result = GetCCStatusCommand::default().execute_async(&mut connection).await;
+        assert!(matches!(result, Err(Error::Io(err)) if err.kind() == std::io::ErrorKind::NotConnected));
+    }
+
+    #[tokio::test]
+    async fn fails_on_rejected_request() {
+        let mut connection = mock_client(vec!["<error>nope</error>"]).await;
+        let result = QuitCommand::default().execute_async(&mut connection).await;
+        assert!(matches!(result, Err(Error::Client(msg)) if msg == "nope"));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::error::{Error, Result};
 use std::io::{ErrorKind, Read, Write};
 use std::net::TcpStream;
+use std::time::{Duration, Instant};
 
 const REPLY_OPENING: &[u8] = b"<boinc_gui_rpc_reply>";
 const REPLY_CLOSING: &[u8] = b"</boinc_gui_rpc_reply>";
@@ -10,63 +11,716 @@ const REQUEST_CLOSING: &[u8] = b"\n</boinc_gui_rpc_request>\x03";
 
 const EOM: u8 = 0x03;
 
+// number of bytes shown from the start and the end of a reply in error messages
+const PREVIEW_LEN: usize = 256;
+
 pub const DEFAULT_PORT: u16 = 31416;
 
+// replies of services users commonly point joinc at by mistake
+const FOREIGN_REPLY_PREFIXES: [&[u8]; 3] = [b"HTTP/", b"<html", b"SSH-"];
+
+/// Exchanges a single GUI RPC request and reply with a client,
+/// the requests and replies are passed without their envelope.
+pub trait RpcTransport {
+    fn do_rpc(&mut self, request: &[u8]) -> Result<Vec<u8>>;
+
+    /// Limits how long sending or receiving a message may block, None blocks indefinitely.
+    /// Exceeding it fails the exchange with an [ErrorKind::WouldBlock] or [ErrorKind::TimedOut] I/O error
+    /// and any further exchange with an [ErrorKind::NotConnected] one, as the rest of the timed out reply
+    /// would be read as the next reply. Transports without a socket ignore it.
+    fn set_timeout(&mut self, _timeout: Option<Duration>) -> Result<()> {
+        Ok(())
+    }
+
+    /// The timeout set by [set_timeout](Self::set_timeout), None if there's none.
+    fn timeout(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Limits how long the exchanges may take until the deadline in total, None removes the limit.
+    /// Passing it fails the exchange like exceeding the timeout, e.g. for a client trickling its reply.
+    /// Transports without a socket ignore it.
+    fn set_deadline(&mut self, _deadline: Option<Instant>) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Cumulative sizes of the messages exchanged over a connection, including the framing.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionStats {
+    pub requests: u64,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+}
+
+// The limits and state of a connection's exchanges.
+#[derive(Default)]
+struct ExchangeState {
+    stats: ConnectionStats,
+    timeout: Option<Duration>,
+    deadline: Option<Instant>,
+    // an exchange timed out, so the rest of its reply would be read as the next one's
+    out_of_sync: bool,
+}
+
+impl ExchangeState {
+    fn set_timeout(&mut self, socket: &TcpStream, timeout: Option<Duration>) -> Result<()> {
+        self.timeout = timeout;
+        set_socket_timeout(socket, timeout)
+    }
+
+    // restores the timeout, which is shrunk while there's a deadline
+    fn set_deadline(&mut self, socket: &TcpStream, deadline: Option<Instant>) -> Result<()> {
+        self.deadline = deadline;
+        set_socket_timeout(socket, self.timeout)
+    }
+
+    // shrinks the timeout to the time left until the deadline, failing if it passed
+    fn limit_timeout(&self, socket: &TcpStream) -> Result<()> {
+        let deadline = match self.deadline {
+            Some(deadline) => deadline,
+            None => return Ok(()),
+        };
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            return Err(Error::Io(ErrorKind::TimedOut.into()));
+        }
+        set_socket_timeout(socket, Some(self.timeout.map_or(left, |timeout| timeout.min(left))))
+    }
+}
+
+// A stream carrying the GUI RPCs over a socket, whose timeouts limit the exchanges.
+trait RpcStream: Read + Write {
+    fn socket(&self) -> &TcpStream;
+}
+
+impl RpcStream for TcpStream {
+    fn socket(&self) -> &TcpStream {
+        self
+    }
+}
+
 pub struct Connection {
     stream: TcpStream,
+    port: u16,
+    state: ExchangeState,
 }
 
 impl Connection {
     pub fn open(host: &str, port: u16) -> Result<Self> {
-        let stream = TcpStream::connect((host, port)).map_err(|err| Error::Io(err))?;
-        Ok(Connection { stream })
+        let stream = TcpStream::connect((host, port)).map_err(Error::Io)?;
+        Ok(Connection { stream, port, state: ExchangeState::default() })
     }
 
     pub fn do_rpc(&mut self, request: &[u8]) -> Result<Vec<u8>> {
-        self.stream.write(REQUEST_OPENING)?;
-        self.stream.write(request)?;
-        self.stream.write(REQUEST_CLOSING)?;
+        exchange(&mut self.stream, self.port, &mut self.state, request)
+    }
+
+    pub fn stats(&self) -> ConnectionStats {
+        self.state.stats
+    }
+}
+
+impl RpcTransport for Connection {
+    fn do_rpc(&mut self, request: &[u8]) -> Result<Vec<u8>> {
+        Connection::do_rpc(self, request)
+    }
+
+    fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+        self.state.set_timeout(&self.stream, timeout)
+    }
+
+    fn timeout(&self) -> Option<Duration> {
+        self.state.timeout
+    }
+
+    fn set_deadline(&mut self, deadline: Option<Instant>) -> Result<()> {
+        self.state.set_deadline(&self.stream, deadline)
+    }
+}
+
+#[cfg(feature = "async")]
+pub use self::asynchronous::AsyncConnection;
+#[cfg(feature = "tls")]
+pub use self::tls::TlsConnection;
+
+#[cfg(feature = "async")]
+mod asynchronous {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    /// The async counterpart of [Connection], framing requests and replies the same way.
+    pub struct AsyncConnection {
+        stream: TcpStream,
+        port: u16,
+        stats: ConnectionStats,
+        // an exchange was dropped before its end, e.g. on a timeout, so the rest of its reply
+        // would be read as the next one's
+        in_exchange: bool,
+    }
+
+    impl AsyncConnection {
+        pub async fn open(host: &str, port: u16) -> Result<Self> {
+            let stream = TcpStream::connect((host, port)).await.map_err(Error::Io)?;
+            Ok(AsyncConnection { stream, port, stats: ConnectionStats::default(), in_exchange: false })
+        }
+
+        pub async fn do_rpc(&mut self, request: &[u8]) -> Result<Vec<u8>> {
+            if self.in_exchange {
+                return Err(Error::Io(std::io::Error::new(
+                    ErrorKind::NotConnected, "the connection is out of sync after a dropped exchange, reconnect")));
+            }
+            self.in_exchange = true;
+            let result = self.exchange(request).await;
+            self.in_exchange = false;
+            result
+        }
 
-        let mut result: Vec<u8> = vec![];
+        async fn exchange(&mut self, request: &[u8]) -> Result<Vec<u8>> {
+            self.stats.requests += 1;
+            self.stream.write_all(REQUEST_OPENING).await?;
+            self.stream.write_all(request).await?;
+            self.stream.write_all(REQUEST_CLOSING).await?;
+            self.stats.bytes_sent += (REQUEST_OPENING.len() + request.len() + REQUEST_CLOSING.len()) as u64;
 
-        {
+            let mut result: Vec<u8> = vec![];
             let mut buffer = [0; 4096];
             loop {
                 let bytes_read = self
                     .stream
                     .read(&mut buffer)
-                    .map_err(|err| Error::Io(err))?;
+                    .await
+                    .map_err(Error::Io)?;
+                self.stats.bytes_received += bytes_read as u64;
 
                 if bytes_read == 0 {
-                    return Err(Error::Io(std::io::Error::from(ErrorKind::UnexpectedEof)));
+                    return Err(closed_early(&result, self.port));
                 }
-
-                let to_write = if buffer[bytes_read - 1] == EOM {
-                    bytes_read - 1
-                } else {
-                    bytes_read
-                };
-
-                result.extend_from_slice(&buffer[..to_write]);
-
-                if bytes_read != to_write {
+                if append_chunk(&mut result, &buffer[..bytes_read]) {
                     break;
                 }
             }
+
+            extract_reply(result, self.port)
+        }
+
+        pub fn stats(&self) -> ConnectionStats {
+            self.stats
+        }
+    }
+}
+
+#[cfg(feature = "socks")]
+impl Connection {
+    /// Connects to the client through the SOCKS5 proxy at `proxy_addr`, e.g. on a bastion host.
+    /// The proxy resolves the client's host name.
+    pub fn open_via_socks5<A>(proxy_addr: A, host: &str, port: u16) -> Result<Self>
+    where
+        A: std::net::ToSocketAddrs,
+    {
+        let mut stream = TcpStream::connect(proxy_addr).map_err(Error::Io)?;
+        socks5_connect(&mut stream, host, port)?;
+        Ok(Connection { stream, port, state: ExchangeState::default() })
+    }
+}
+
+// Asks the proxy to connect to the host without authentication, see RFC 1928.
+#[cfg(feature = "socks")]
+fn socks5_connect<S>(stream: &mut S, host: &str, port: u16) -> Result<()>
+where
+    S: Read + Write,
+{
+    const VERSION: u8 = 5;
+    const NO_AUTHENTICATION: u8 = 0;
+    const CONNECT: u8 = 1;
+    const IPV4: u8 = 1;
+    const DOMAIN_NAME: u8 = 3;
+    const IPV6: u8 = 4;
+    const SUCCEEDED: u8 = 0;
+
+    stream.write_all(&[VERSION, 1, NO_AUTHENTICATION])?;
+    let mut method = [0u8; 2];
+    stream.read_exact(&mut method)?;
+    if method != [VERSION, NO_AUTHENTICATION] {
+        return Err(Error::Rpc("SOCKS5 proxy requires an unsupported authentication".to_string()));
+    }
+
+    let host_len = u8::try_from(host.len())
+        .map_err(|_| Error::InvalidArgument(format!("Host name too long for SOCKS5: {}", host)))?;
+    let mut request = vec![VERSION, CONNECT, 0, DOMAIN_NAME, host_len];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request)?;
+
+    let mut reply = [0u8; 4];
+    stream.read_exact(&mut reply)?;
+    if reply[0] != VERSION {
+        return Err(Error::Rpc(format!("Not a SOCKS5 reply (version {})", reply[0])));
+    }
+    if reply[1] != SUCCEEDED {
+        return Err(Error::Rpc(format!("SOCKS5 proxy failed to connect to {}:{} (reply {})", host, port, reply[1])));
+    }
+
+    // the address the proxy bound to isn't of interest
+    let address_len = match reply[3] {
+        IPV4 => 4,
+        IPV6 => 16,
+        DOMAIN_NAME => {
+            let mut len = [0u8];
+            stream.read_exact(&mut len)?;
+            len[0] as usize
+        }
+        other => return Err(Error::Rpc(format!("SOCKS5 proxy replied an unknown address type {}", other))),
+    };
+    stream.read_exact(&mut vec![0u8; address_len + 2])?;
+
+    Ok(())
+}
+
+#[cfg(feature = "tls")]
+mod tls {
+    use super::*;
+    use rustls::pki_types::ServerName;
+    use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::path::Path;
+    use std::sync::Arc;
+
+    /// A connection to a client exposed through a TLS tunnel, e.g. by stunnel.
+    pub struct TlsConnection {
+        stream: StreamOwned<ClientConnection, TcpStream>,
+        port: u16,
+        state: ExchangeState,
+    }
+
+    impl RpcStream for StreamOwned<ClientConnection, TcpStream> {
+        fn socket(&self) -> &TcpStream {
+            &self.sock
+        }
+    }
+
+    impl TlsConnection {
+        /// Connects to the tunnel's endpoint, verifying its certificate against
+        /// the PEM encoded certificates in `ca_file` or the Mozilla root certificates if not given.
+        pub fn open(host: &str, port: u16, ca_file: Option<&Path>) -> Result<Self> {
+            let roots = match ca_file {
+                Some(path) => load_certificates(path)?,
+                None => RootCertStore {
+                    roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+                },
+            };
+            let config = ClientConfig::builder()
+                .with_root_certificates(roots)
+                .with_no_client_auth();
+            let server_name = ServerName::try_from(host.to_string())
+                .map_err(|_| Error::InvalidArgument(format!("Invalid server name {}", host)))?;
+            let connection = ClientConnection::new(Arc::new(config), server_name)
+                .map_err(|err| Error::Io(std::io::Error::other(err)))?;
+
+            let socket = TcpStream::connect((host, port)).map_err(Error::Io)?;
+            Ok(TlsConnection {
+                stream: StreamOwned::new(connection, socket),
+                port,
+                state: ExchangeState::default(),
+            })
+        }
+
+        pub fn do_rpc(&mut self, request: &[u8]) -> Result<Vec<u8>> {
+            exchange(&mut self.stream, self.port, &mut self.state, request)
+        }
+
+        /// The sizes of the messages inside the tunnel, i.e. without the overhead of TLS.
+        pub fn stats(&self) -> ConnectionStats {
+            self.state.stats
+        }
+    }
+
+    impl RpcTransport for TlsConnection {
+        fn do_rpc(&mut self, request: &[u8]) -> Result<Vec<u8>> {
+            TlsConnection::do_rpc(self, request)
+        }
+
+        fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<()> {
+            self.state.set_timeout(&self.stream.sock, timeout)
+        }
+
+        fn timeout(&self) -> Option<Duration> {
+            self.state.timeout
         }
 
-        let opening = result
-            .windows(REPLY_OPENING.len())
-            .position(|w| w == REPLY_OPENING)
-            .ok_or(Error::Rpc(format!("Not a GUI RPC response: {:?}", result)))?;
-        let closing = result
-            .windows(REPLY_CLOSING.len())
-            .rposition(|w| w == REPLY_CLOSING)
-            .ok_or(Error::Rpc(format!("Not a GUI RPC response: {:?}", result)))?;
+        fn set_deadline(&mut self, deadline: Option<Instant>) -> Result<()> {
+            self.state.set_deadline(&self.stream.sock, deadline)
+        }
+    }
 
-        result.drain(closing..);
-        result.drain(..opening + REPLY_OPENING.len());
+    fn load_certificates(path: &Path) -> Result<RootCertStore> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut roots = RootCertStore::empty();
+        for certificate in rustls_pemfile::certs(&mut reader) {
+            roots
+                .add(certificate?)
+                .map_err(|err| Error::InvalidArgument(format!("Invalid certificate in {}: {}", path.display(), err)))?;
+        }
+        if roots.is_empty() {
+            return Err(Error::InvalidArgument(format!("No certificates found in {}", path.display())));
+        }
+        Ok(roots)
+    }
+}
+
+fn set_socket_timeout(stream: &TcpStream, timeout: Option<Duration>) -> Result<()> {
+    stream.set_read_timeout(timeout)?;
+    stream.set_write_timeout(timeout)?;
+    Ok(())
+}
+
+// Sends the request and reads the reply in the framing of the GUI RPCs.
+// A timed out exchange leaves the connection out of sync, failing any further exchange.
+fn exchange<S>(stream: &mut S, port: u16, state: &mut ExchangeState, request: &[u8]) -> Result<Vec<u8>>
+where
+    S: RpcStream,
+{
+    if state.out_of_sync {
+        return Err(Error::Io(std::io::Error::new(
+            ErrorKind::NotConnected, "the connection is out of sync after a timeout, reconnect")));
+    }
+    state.stats.requests += 1;
+    // nothing was sent yet, so a deadline passed already leaves the connection in sync
+    state.limit_timeout(stream.socket())?;
+    let result = exchange_within_limits(stream, port, state, request);
+    if let Err(Error::Io(err)) = &result {
+        state.out_of_sync |= matches!(err.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock);
+    }
+    result
+}
+
+fn exchange_within_limits<S>(stream: &mut S, port: u16, state: &mut ExchangeState, request: &[u8]) -> Result<Vec<u8>>
+where
+    S: RpcStream,
+{
+    stream.write_all(REQUEST_OPENING)?;
+    stream.write_all(request)?;
+    stream.write_all(REQUEST_CLOSING)?;
+    state.stats.bytes_sent += (REQUEST_OPENING.len() + request.len() + REQUEST_CLOSING.len()) as u64;
+
+    let mut result: Vec<u8> = vec![];
+    let mut buffer = [0; 4096];
+    loop {
+        state.limit_timeout(stream.socket())?;
+        let bytes_read = stream
+            .read(&mut buffer)
+            .map_err(Error::Io)?;
+        state.stats.bytes_received += bytes_read as u64;
+
+        if bytes_read == 0 {
+            return Err(closed_early(&result, port));
+        }
+        if append_chunk(&mut result, &buffer[..bytes_read]) {
+            break;
+        }
+    }
+
+    extract_reply(result, port)
+}
+
+// Appends a chunk read from the client to the reply and returns whether the reply is complete.
+fn append_chunk(result: &mut Vec<u8>, chunk: &[u8]) -> bool {
+    match chunk.split_last() {
+        Some((&EOM, rest)) => {
+            result.extend_from_slice(rest);
+            true
+        }
+        _ => {
+            result.extend_from_slice(chunk);
+            false
+        }
+    }
+}
+
+// The error for a connection closed by the client before the end of the reply.
+fn closed_early(result: &[u8], port: u16) -> Error {
+    if is_foreign_reply(result) {
+        return foreign_service(port);
+    }
+    if find(result, REPLY_OPENING).is_some() {
+        return truncated_reply(result);
+    }
+    Error::Io(std::io::Error::from(ErrorKind::UnexpectedEof))
+}
+
+// Strips the reply's envelope.
+fn extract_reply(mut result: Vec<u8>, port: u16) -> Result<Vec<u8>> {
+    if is_foreign_reply(&result) {
+        return Err(foreign_service(port));
+    }
+
+    let opening = find(&result, REPLY_OPENING).ok_or_else(|| {
+        Error::Rpc(format!("Not a GUI RPC response ({} bytes): {:?}", result.len(), preview(&result)))
+    })?;
+    let closing = result
+        .windows(REPLY_CLOSING.len())
+        .rposition(|w| w == REPLY_CLOSING)
+        .ok_or_else(|| truncated_reply(&result))?;
+
+    result.drain(closing..);
+    result.drain(..opening + REPLY_OPENING.len());
+
+    Ok(result)
+}
+
+fn foreign_service(port: u16) -> Error {
+    Error::Rpc(format!("port {} does not appear to be a BOINC GUI RPC endpoint", port))
+}
+
+fn is_foreign_reply(result: &[u8]) -> bool {
+    let start = result.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(result.len());
+    FOREIGN_REPLY_PREFIXES
+        .iter()
+        .any(|prefix| result[start..].get(..prefix.len()).is_some_and(|head| head.eq_ignore_ascii_case(prefix)))
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+fn truncated_reply(result: &[u8]) -> Error {
+    Error::Rpc(format!("truncated reply ({} bytes)", result.len()))
+}
+
+fn preview(bytes: &[u8]) -> String {
+    if bytes.len() <= 2 * PREVIEW_LEN {
+        String::from_utf8_lossy(bytes).into_owned()
+    } else {
+        format!("{}...{}",
+            String::from_utf8_lossy(&bytes[..PREVIEW_LEN]),
+            String::from_utf8_lossy(&bytes[bytes.len() - PREVIEW_LEN..]))
+    }
+}
+
+// ----- Tests -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::testing::serve_raw_replies;
+    use std::net::TcpListener;
+    use std::thread;
+
+    // Replies once with the given bytes and closes the connection afterwards.
+    fn mock_client(reply: &[u8]) -> Connection {
+        Connection::open("127.0.0.1", serve_raw_replies(vec![reply.to_vec()])).unwrap()
+    }
+
+    #[test]
+    fn extracts_reply() {
+        let mut connection = mock_client(b"<boinc_gui_rpc_reply><success/></boinc_gui_rpc_reply>\x03");
+        assert_eq!(connection.do_rpc(b"<ping/>").unwrap(), b"<success/>");
+    }
+
+    #[test]
+    fn counts_exchanged_bytes() {
+        const REPLY: &[u8] = b"<boinc_gui_rpc_reply><success/></boinc_gui_rpc_reply>\x03";
+        let mut connection = Connection::open("127.0.0.1", serve_raw_replies(vec![REPLY.to_vec(); 2])).unwrap();
+        assert_eq!(connection.stats(), ConnectionStats::default());
+        connection.do_rpc(b"<ping/>").unwrap();
+        connection.do_rpc(b"<get_cc_status/>").unwrap();
+
+        let framing = (REQUEST_OPENING.len() + REQUEST_CLOSING.len()) as u64;
+        assert_eq!(connection.stats(), ConnectionStats {
+            requests: 2,
+            bytes_sent: 2 * framing + 7 + 16,
+            bytes_received: 2 * REPLY.len() as u64,
+        });
+    }
+
+    #[test]
+    fn stays_in_sync_if_deadline_passed_before_sending() {
+        let mut connection = mock_client(b"<boinc_gui_rpc_reply><success/></boinc_gui_rpc_reply>\x03");
+        connection.set_deadline(Some(Instant::now())).unwrap();
+        let result = connection.do_rpc(b"<ping/>");
+        assert!(matches!(result, Err(Error::Io(err)) if err.kind() == ErrorKind::TimedOut));
+
+        connection.set_deadline(None).unwrap();
+        assert_eq!(connection.do_rpc(b"<ping/>").unwrap(), b"<success/>");
+    }
+
+    #[test]
+    fn fails_on_reply_closed_early() {
+        let mut connection = mock_client(b"<boinc_gui_rpc_reply><cc_status>");
+        let result = connection.do_rpc(b"<get_cc_status/>");
+        assert!(matches!(result, Err(Error::Rpc(msg)) if msg == "truncated reply (32 bytes)"));
+    }
+
+    #[test]
+    fn fails_on_reply_missing_its_closing_tag() {
+        let mut connection = mock_client(b"<boinc_gui_rpc_reply><cc_status>\x03");
+        let result = connection.do_rpc(b"<get_cc_status/>");
+        assert!(matches!(result, Err(Error::Rpc(msg)) if msg == "truncated reply (32 bytes)"));
+    }
+
+    #[test]
+    fn detects_http_server() {
+        let mut connection = mock_client(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n");
+        let port = connection.port;
+        let result = connection.do_rpc(b"<get_cc_status/>");
+        assert!(matches!(result, Err(Error::Rpc(msg))
+            if msg == format!("port {} does not appear to be a BOINC GUI RPC endpoint", port)));
+    }
+
+    #[test]
+    fn detects_html_reply() {
+        let mut connection = mock_client(b"\n<HTML><body>It works!</body></HTML>\x03");
+        let result = connection.do_rpc(b"<get_cc_status/>");
+        assert!(matches!(result, Err(Error::Rpc(msg)) if msg.contains("does not appear to be a BOINC GUI RPC endpoint")));
+        assert!(is_foreign_reply(b"SSH-2.0-OpenSSH_9.6\r\n"));
+        assert!(!is_foreign_reply(b"<boinc_gui_rpc_reply>"));
+    }
+
+    #[test]
+    fn bounds_preview_of_non_conforming_reply() {
+        assert_eq!(preview(b"foo"), "foo");
+
+        let mut reply = vec![b'a'; PREVIEW_LEN];
+        reply.extend_from_slice(&[b'b'; 10000]);
+        reply.extend_from_slice(&[b'c'; PREVIEW_LEN]);
+        assert_eq!(preview(&reply), format!("{}...{}", "a".repeat(PREVIEW_LEN), "c".repeat(PREVIEW_LEN)));
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn exchanges_over_tls() {
+        use rcgen::{BasicConstraints, Certificate, CertificateParams, IsCa};
+        use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+        use rustls::{ServerConfig, ServerConnection, StreamOwned};
+        use std::sync::Arc;
+
+        let mut ca_params = CertificateParams::new(vec![]);
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        let ca = Certificate::from_params(ca_params).unwrap();
+        let server = Certificate::from_params(CertificateParams::new(vec!["localhost".to_string()])).unwrap();
+        let server_key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(server.serialize_private_key_der()));
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(vec![CertificateDer::from(server.serialize_der_with_signer(&ca).unwrap())], server_key)
+            .unwrap();
+
+        let ca_file = std::env::temp_dir().join(format!("joinc-test-ca-{}.pem", std::process::id()));
+        std::fs::write(&ca_file, ca.serialize_pem().unwrap()).unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (socket, _) = listener.accept().unwrap();
+            let mut stream = StreamOwned::new(ServerConnection::new(Arc::new(config)).unwrap(), socket);
+            let mut byte = [0u8];
+            while stream.read(&mut byte).unwrap() == 1 && byte[0] != EOM {}
+            stream.write_all(b"<boinc_gui_rpc_reply><success/></boinc_gui_rpc_reply>\x03").unwrap();
+        });
+
+        let connection = TlsConnection::open("localhost", port, Some(&ca_file));
+        std::fs::remove_file(&ca_file).unwrap();
+        assert_eq!(connection.unwrap().do_rpc(b"<ping/>").unwrap(), b"<success/>");
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn rejects_invalid_tls_arguments() {
+        let result = TlsConnection::open("not a host name", 1, None);
+        assert!(matches!(result, Err(Error::InvalidArgument(msg)) if msg == "Invalid server name not a host name"));
+
+        let ca_file = std::env::temp_dir().join(format!("joinc-test-empty-ca-{}.pem", std::process::id()));
+        std::fs::write(&ca_file, "").unwrap();
+        let result = TlsConnection::open("localhost", 1, Some(&ca_file));
+        std::fs::remove_file(&ca_file).unwrap();
+        assert!(matches!(result, Err(Error::InvalidArgument(msg)) if msg.starts_with("No certificates found")));
+
+        let result = TlsConnection::open("localhost", 1, Some(&ca_file));
+        assert!(matches!(result, Err(Error::Io(err)) if err.kind() == ErrorKind::NotFound));
+    }
+
+    #[cfg(feature = "socks")]
+    #[test]
+    fn connects_via_socks5() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy_port = listener.local_addr().unwrap().port();
+
+        // accepts the connect request and stands in for the client itself
+        let proxy = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut greeting = [0u8; 3];
+            stream.read_exact(&mut greeting).unwrap();
+            stream.write_all(&[5, 0]).unwrap();
+
+            let mut request = [0u8; 5 + 10 + 2];
+            stream.read_exact(&mut request).unwrap();
+            stream.write_all(&[5, 0, 0, 1, 127, 0, 0, 1, 0x7a, 0xb8]).unwrap();
+
+            let mut byte = [0u8];
+            while stream.read(&mut byte).unwrap() == 1 && byte[0] != EOM {}
+            stream.write_all(b"<boinc_gui_rpc_reply><success/></boinc_gui_rpc_reply>\x03").unwrap();
+            (greeting, request)
+        });
+
+        let mut connection = Connection::open_via_socks5(("127.0.0.1", proxy_port), "client.lan", 31416).unwrap();
+        assert_eq!(connection.do_rpc(b"<ping/>").unwrap(), b"<success/>");
+
+        let (greeting, request) = proxy.join().unwrap();
+        assert_eq!(greeting, [5, 1, 0]);
+        assert_eq!(request, *b"\x05\x01\x00\x03\x0aclient.lan\x7a\xb8");
+    }
+
+    // replies with the given bytes and ignores the requests
+    #[cfg(feature = "socks")]
+    struct FakeProxy(std::io::Cursor<Vec<u8>>);
+
+    #[cfg(feature = "socks")]
+    impl Read for FakeProxy {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.0.read(buf)
+        }
+    }
+
+    #[cfg(feature = "socks")]
+    impl Write for FakeProxy {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "socks")]
+    #[test]
+    fn fails_on_socks5_connect_refused() {
+        let mut proxy = FakeProxy(std::io::Cursor::new(vec![5, 0, 5, 5, 0, 1]));
+        let result = socks5_connect(&mut proxy, "client.lan", 31416);
+        assert!(matches!(result, Err(Error::Rpc(msg)) if msg == "SOCKS5 proxy failed to connect to client.lan:31416 (reply 5)"));
+    }
+
+    #[cfg(feature = "socks")]
+    #[test]
+    fn fails_on_reply_of_other_socks_version() {
+        let mut proxy = FakeProxy(std::io::Cursor::new(vec![5, 0, 4, 0, 0, 1, 127, 0, 0, 1, 0x7a, 0xb8]));
+        let result = socks5_connect(&mut proxy, "client.lan", 31416);
+        assert!(matches!(result, Err(Error::Rpc(msg)) if msg == "Not a SOCKS5 reply (version 4)"));
+    }
+
+    #[cfg(feature = "socks")]
+    #[test]
+    fn refuses_host_names_too_long_for_socks5() {
+        let mut proxy = FakeProxy(std::io::Cursor::new(vec![5, 0]));
+        let result = socks5_connect(&mut proxy, &"a".repeat(256), 31416);
+        assert!(matches!(result, Err(Error::InvalidArgument(msg)) if msg.starts_with("Host name too long for SOCKS5")));
+    }
 
-        Ok(result)
+    #[test]
+    fn reports_length_of_non_conforming_reply() {
+        let mut connection = mock_client(b"garbage\x03");
+        let result = connection.do_rpc(b"<get_cc_status/>");
+        assert!(matches!(result, Err(Error::Rpc(msg)) if msg == "Not a GUI RPC response (7 bytes): \"garbage\""));
     }
 }
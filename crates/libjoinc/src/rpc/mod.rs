@@ -1,2 +1,5 @@
 pub mod commands;
 pub mod connection;
+pub mod pool;
+#[cfg(any(test, feature = "test-util"))]
+pub mod testing;
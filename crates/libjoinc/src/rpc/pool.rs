@@ -0,0 +1,209 @@
+use crate::error::Result;
+use crate::rpc::commands::AuthorizeCommand;
+use crate::rpc::connection::{Connection, RpcTransport};
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+
+type Connector<T> = Box<dyn Fn() -> Result<T> + Send + Sync>;
+
+/// Keeps up to `size` authenticated connections to a single client for reuse,
+/// saving the reconnection and authorization of each request of frequent polling.
+pub struct ConnectionPool<T = Connection> {
+    connect: Connector<T>,
+    password: Option<String>,
+    size: usize,
+    idle: Mutex<Vec<T>>,
+}
+
+impl ConnectionPool {
+    pub fn new(host: &str, port: u16, password: Option<String>, size: usize) -> Self {
+        let host = host.to_string();
+        Self::with_connector(move || Connection::open(&host, port), password, size)
+    }
+}
+
+impl<T> ConnectionPool<T>
+where
+    T: RpcTransport,
+{
+    /// Creates a pool opening its connections with the given function.
+    pub fn with_connector<F>(connect: F, password: Option<String>, size: usize) -> Self
+    where
+        F: Fn() -> Result<T> + Send + Sync + 'static,
+    {
+        ConnectionPool {
+            connect: Box::new(connect),
+            password,
+            size,
+            idle: Mutex::new(vec![]),
+        }
+    }
+
+    /// Hands out an idle connection or a newly opened and authenticated one if there is none.
+    pub fn get(&self) -> Result<PooledConnection<'_, T>> {
+        let idle = self.idle.lock().unwrap_or_else(|err| err.into_inner()).pop();
+        let connection = match idle {
+            Some(connection) => connection,
+            None => self.open()?,
+        };
+        Ok(PooledConnection {
+            pool: self,
+            connection: Some(connection),
+            broken: false,
+        })
+    }
+
+    /// The number of connections waiting to be handed out.
+    pub fn idle_count(&self) -> usize {
+        self.idle.lock().unwrap_or_else(|err| err.into_inner()).len()
+    }
+
+    fn open(&self) -> Result<T> {
+        let mut connection = (self.connect)()?;
+        if let Some(password) = &self.password {
+            AuthorizeCommand::new(password.as_str()).execute(&mut connection)?;
+        }
+        Ok(connection)
+    }
+
+    fn put_back(&self, connection: T) {
+        let mut idle = self.idle.lock().unwrap_or_else(|err| err.into_inner());
+        if idle.len() < self.size {
+            idle.push(connection);
+        }
+    }
+}
+
+/// A connection of a [ConnectionPool], which is returned to the pool on drop.
+///
+/// A connection failing to exchange a request is considered dead
+/// and dropped instead, so the pool opens a new one when needed.
+pub struct PooledConnection<'a, T>
+where
+    T: RpcTransport,
+{
+    pool: &'a ConnectionPool<T>,
+    connection: Option<T>,
+    broken: bool,
+}
+
+impl<T> RpcTransport for PooledConnection<'_, T>
+where
+    T: RpcTransport,
+{
+    fn do_rpc(&mut self, request: &[u8]) -> Result<Vec<u8>> {
+        let result = self.deref_mut().do_rpc(request);
+        self.broken |= result.is_err();
+        result
+    }
+
+    fn set_timeout(&mut self, timeout: Option<std::time::Duration>) -> Result<()> {
+        self.deref_mut().set_timeout(timeout)
+    }
+
+    fn timeout(&self) -> Option<std::time::Duration> {
+        self.deref().timeout()
+    }
+
+    fn set_deadline(&mut self, deadline: Option<std::time::Instant>) -> Result<()> {
+        self.deref_mut().set_deadline(deadline)
+    }
+}
+
+impl<T> Deref for PooledConnection<'_, T>
+where
+    T: RpcTransport,
+{
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.connection.as_ref().expect("connection is only taken on drop")
+    }
+}
+
+impl<T> DerefMut for PooledConnection<'_, T>
+where
+    T: RpcTransport,
+{
+    fn deref_mut(&mut self) -> &mut T {
+        self.connection.as_mut().expect("connection is only taken on drop")
+    }
+}
+
+impl<T> Drop for PooledConnection<'_, T>
+where
+    T: RpcTransport,
+{
+    fn drop(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            if !self.broken {
+                self.pool.put_back(connection);
+            }
+        }
+    }
+}
+
+// ----- Tests -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::rpc::commands::{Command, GetCCStatusCommand};
+    use crate::rpc::testing::ScriptedTransport;
+
+    // Opens the transports in turn, which authorize any password and reply with an empty status,
+    // or fail like a dead connection for the ones in `dead`.
+    fn fake_pool(password: Option<&str>, dead: &[usize]) -> (ConnectionPool<ScriptedTransport>, Vec<ScriptedTransport>) {
+        let transports: Vec<ScriptedTransport> = (0..3)
+            .map(|idx| if dead.contains(&idx) {
+                ScriptedTransport::new().authorizing().fail(Error::Io(std::io::ErrorKind::ConnectionReset.into()))
+            } else {
+                ScriptedTransport::new().authorizing().reply("<cc_status/>")
+            })
+            .collect();
+        let unopened = Mutex::new(transports.clone().into_iter());
+        let pool = ConnectionPool::with_connector(
+            move || Ok(unopened.lock().unwrap().next().expect("no further connection scripted")),
+            password.map(str::to_string),
+            2,
+        );
+        (pool, transports)
+    }
+
+    #[test]
+    fn authenticates_new_connections() {
+        let (pool, transports) = fake_pool(Some("secret"), &[]);
+        GetCCStatusCommand::default().execute(&mut pool.get().unwrap()).unwrap();
+        assert_eq!(transports[0].request_tags(), ["auth1", "auth2", "get_cc_status"]);
+    }
+
+    #[test]
+    fn reuses_live_connections() {
+        let (pool, transports) = fake_pool(Some("secret"), &[]);
+        GetCCStatusCommand::default().execute(&mut pool.get().unwrap()).unwrap();
+        GetCCStatusCommand::default().execute(&mut pool.get().unwrap()).unwrap();
+        assert_eq!(transports[0].request_tags()[3..], ["get_cc_status"]);
+        assert!(transports[1].requests().is_empty());
+        assert_eq!(pool.idle_count(), 1);
+    }
+
+    #[test]
+    fn replaces_dead_connections() {
+        let (pool, transports) = fake_pool(Some("secret"), &[0]);
+        assert!(GetCCStatusCommand::default().execute(&mut pool.get().unwrap()).is_err());
+        assert_eq!(pool.idle_count(), 0);
+
+        GetCCStatusCommand::default().execute(&mut pool.get().unwrap()).unwrap();
+        assert_eq!(transports[0].request_tags(), ["auth1", "auth2", "get_cc_status"]);
+        assert_eq!(transports[1].request_tags(), ["auth1", "auth2", "get_cc_status"]);
+    }
+
+    #[test]
+    fn keeps_at_most_size_connections() {
+        let (pool, _) = fake_pool(None, &[]);
+        let connections: Vec<_> = (0..3).map(|_| pool.get().unwrap()).collect();
+        drop(connections);
+        assert_eq!(pool.idle_count(), 2);
+    }
+}
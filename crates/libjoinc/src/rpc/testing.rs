@@ -0,0 +1,217 @@
+//! Helpers to test code built on the rpc layer without a client.
+
+use crate::error::{Error, Result};
+use crate::rpc::commands::Clock;
+use crate::rpc::connection::RpcTransport;
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A clock advancing only when sleeping.
+pub struct FakeClock(Cell<Instant>);
+
+impl FakeClock {
+    pub fn new(start: Instant) -> Self {
+        Self(Cell::new(start))
+    }
+}
+
+impl Default for FakeClock {
+    fn default() -> Self {
+        Self::new(Instant::now())
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.0.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.0.set(self.0.get() + duration)
+    }
+}
+
+/// A transport replying to the requests by a script instead of a client, logging the requests.
+///
+/// The replies are scripted for the requests of a tag, e.g. `get_results`, or for any request.
+/// Each request takes the next reply scripted for its tag, else the next one for any request.
+/// The last scripted reply answers all further requests, while a scripted failure is used once.
+/// A request without any reply fails like a client closing the connection.
+///
+/// The clones share the script and the log, e.g. to inspect the requests of a transport moved into a pool.
+#[derive(Clone, Default)]
+pub struct ScriptedTransport(Arc<Mutex<Script>>);
+
+// the replies still scripted for a tag, None for any request
+type Replies = (Option<String>, VecDeque<Result<Vec<u8>>>);
+
+#[derive(Default)]
+struct Script {
+    replies: Vec<Replies>,
+    requests: Vec<String>,
+}
+
+impl ScriptedTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replies to the requests with the given replies in turn.
+    pub fn replying<'a, I>(replies: I) -> Self
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        replies.into_iter().fold(Self::new(), Self::reply)
+    }
+
+    /// Replies to the authorization, accepting any password.
+    pub fn authorizing(self) -> Self {
+        self.reply_to("auth1", "<nonce>123</nonce>").reply_to("auth2", "<authorized/>")
+    }
+
+    /// Adds a reply to any request.
+    pub fn reply(self, reply: &str) -> Self {
+        self.push(None, Ok(reply.as_bytes().to_vec()))
+    }
+
+    /// Adds a reply to the requests with the tag.
+    pub fn reply_to(self, tag: &str, reply: &str) -> Self {
+        self.push(Some(tag), Ok(reply.as_bytes().to_vec()))
+    }
+
+    /// Adds a failure of any request.
+    pub fn fail(self, err: Error) -> Self {
+        self.push(None, Err(err))
+    }
+
+    /// Adds a failure of the requests with the tag.
+    pub fn fail_to(self, tag: &str, err: Error) -> Self {
+        self.push(Some(tag), Err(err))
+    }
+
+    /// The requests received so far.
+    pub fn requests(&self) -> Vec<String> {
+        self.0.lock().unwrap().requests.clone()
+    }
+
+    /// The tags of the requests received so far, e.g. `auth1`.
+    pub fn request_tags(&self) -> Vec<String> {
+        self.requests().iter().map(|request| tag_of(request).to_string()).collect()
+    }
+
+    fn push(self, tag: Option<&str>, reply: Result<Vec<u8>>) -> Self {
+        {
+            let mut script = self.0.lock().unwrap();
+            let tag = tag.map(str::to_string);
+            match script.replies.iter_mut().find(|(scripted, _)| *scripted == tag) {
+                Some((_, replies)) => replies.push_back(reply),
+                None => script.replies.push((tag, VecDeque::from([reply]))),
+            }
+        }
+        self
+    }
+}
+
+impl RpcTransport for ScriptedTransport {
+    fn do_rpc(&mut self, request: &[u8]) -> Result<Vec<u8>> {
+        let request = String::from_utf8(request.to_vec()).expect("requests are UTF-8");
+        let mut script = self.0.lock().unwrap();
+        let tag = tag_of(&request).to_string();
+        script.requests.push(request);
+
+        let scripted = [Some(tag.clone()), None].iter().find_map(|key| {
+            script.replies.iter().position(|(scripted, replies)| scripted == key && !replies.is_empty())
+        });
+        let replies = match scripted {
+            Some(idx) => &mut script.replies[idx].1,
+            None => return Err(Error::Io(std::io::Error::new(ErrorKind::UnexpectedEof, format!("no reply scripted for {}", tag)))),
+        };
+        match replies.front() {
+            Some(Ok(reply)) if replies.len() == 1 => Ok(reply.clone()),
+            _ => replies.pop_front().expect("not empty"),
+        }
+    }
+}
+
+// the name of the request's outermost element, e.g. get_cc_status of <get_cc_status/>
+fn tag_of(request: &str) -> &str {
+    let request = request.trim_start().trim_start_matches('<');
+    let end = request.find(|c: char| c == '>' || c == '/' || c.is_whitespace()).unwrap_or(request.len());
+    &request[..end]
+}
+
+/// Serves a single connection on a local port like a client, answering each request with the next
+/// of the replies verbatim, i.e. they include the envelope and the end of message byte.
+/// Returns the port, the connection is closed after the last reply.
+pub fn serve_raw_replies(replies: Vec<Vec<u8>>) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        for reply in replies {
+            let mut byte = [0u8];
+            while stream.read(&mut byte).unwrap() == 1 && byte[0] != 0x03 {}
+            stream.write_all(&reply).unwrap();
+        }
+    });
+
+    port
+}
+
+/// Like [serve_raw_replies], wrapping each reply into the envelope.
+pub fn serve_replies<'a, I>(replies: I) -> u16
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    serve_raw_replies(replies
+        .into_iter()
+        .map(|reply| format!("<boinc_gui_rpc_reply>\n{}\n</boinc_gui_rpc_reply>\n\x03", reply).into_bytes())
+        .collect())
+}
+
+// ----- Tests -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replies_by_tag_before_any_request() {
+        let mut transport = ScriptedTransport::new()
+            .reply_to("get_results", "<results>1</results>")
+            .reply_to("get_results", "<results>2</results>")
+            .reply("<success/>");
+
+        assert_eq!(transport.do_rpc(b"<get_results>\n<active_only>0</active_only>\n</get_results>").unwrap(), b"<results>1</results>");
+        assert_eq!(transport.do_rpc(b"<quit/>").unwrap(), b"<success/>");
+        assert_eq!(transport.do_rpc(b"<get_results/>").unwrap(), b"<results>2</results>");
+        assert_eq!(transport.do_rpc(b"<get_results/>").unwrap(), b"<results>2</results>");
+        assert_eq!(transport.request_tags(), ["get_results", "quit", "get_results", "get_results"]);
+    }
+
+    #[test]
+    fn fails_once_per_scripted_failure() {
+        let mut transport = ScriptedTransport::new()
+            .authorizing()
+            .fail_to("get_cc_status", Error::Io(ErrorKind::TimedOut.into()))
+            .reply_to("get_cc_status", "<cc_status/>");
+
+        assert_eq!(transport.do_rpc(b"<auth1/>").unwrap(), b"<nonce>123</nonce>");
+        assert!(matches!(transport.do_rpc(b"<get_cc_status/>"), Err(Error::Io(err)) if err.kind() == ErrorKind::TimedOut));
+        assert_eq!(transport.do_rpc(b"<get_cc_status/>").unwrap(), b"<cc_status/>");
+        assert!(matches!(transport.do_rpc(b"<quit/>"), Err(Error::Io(err)) if err.kind() == ErrorKind::UnexpectedEof));
+    }
+
+    #[test]
+    fn shares_log_with_clones() {
+        let transport = ScriptedTransport::replying(["<success/>"]);
+        let mut clone = transport.clone();
+        clone.do_rpc(b"<run_benchmarks/>").unwrap();
+        assert_eq!(transport.requests(), ["<run_benchmarks/>"]);
+    }
+}
@@ -1,8 +1,11 @@
 use crate::defs::*;
-use serde::{Deserialize, Serialize};
+use crate::error::{Error, Result};
+use serde::de::{IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt::{self, Display};
+use std::ops::Range;
 
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(default)]
 pub struct ActiveTask {
     pub active_task_state: ActiveTaskState,
@@ -27,6 +30,171 @@ pub struct ActiveTask {
     pub checkpoint_cpu_time: Timestamp,
 }
 
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct App {
+    pub non_cpu_intensive: Bool,
+
+    pub name: String,
+    pub user_friendly_name: String,
+}
+
+// An <app> of a project's app_config.xml
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub max_concurrent: i32,
+
+    pub gpu_versions: Option<GpuVersions>,
+
+    pub name: String,
+}
+
+// An <app_version> of a project's app_config.xml
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct AppVersionConfig {
+    pub avg_ncpus: f64,
+    pub ngpus: f64,
+
+    pub app_name: String,
+    pub plan_class: String,
+}
+
+// BOINC's 'CC_STATE', reduced to the parts joinc displays
+#[derive(Debug, Default)]
+pub struct CCState {
+    pub host_info: HostInfo,
+    pub net_stats: NetStats,
+    pub time_stats: TimeStats,
+
+    pub apps: Vec<App>,
+    pub projects: Vec<Project>,
+    pub tasks: Vec<Task>,
+
+    /// The platforms the client can run app versions of, the primary one first.
+    pub platforms: Vec<String>,
+}
+
+// The common options of the client's cc_config.xml, unset options are left to the client's defaults.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename = "options")]
+pub struct CcConfig {
+    pub report_results_immediately: Bool,
+
+    pub http_transfer_timeout: Option<i32>,
+    pub max_file_xfers: Option<i32>,
+    pub max_file_xfers_per_project: Option<i32>,
+    pub ncpus: Option<i32>,
+
+    pub exclude_gpu: Vec<ExcludeGpu>,
+}
+
+/// The client's cc_config.xml as far as it's modeled, other options are dropped, see [RawCcConfig] to keep them.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CcConfigFile {
+    pub log_flags: LogFlags,
+    pub options: CcConfig,
+}
+
+/// The client's cc_config.xml verbatim, keeping the options and log flags joinc doesn't model.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RawCcConfig(String);
+
+impl RawCcConfig {
+    /// Takes the document as sent by the client, failing if it isn't a readable cc_config.
+    pub fn new(xml: String) -> Result<Self> {
+        let _: CcConfigFile = libjoincserde::from_str(&xml)?;
+        Ok(Self(xml))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The modeled parts of the document.
+    pub fn parse(&self) -> Result<CcConfigFile> {
+        Ok(libjoincserde::from_str(&self.0)?)
+    }
+
+    /// Enables or disables the log flag of the given name, leaving the rest of the document untouched.
+    /// Fails without changing anything if the flag can't be located unambiguously, e.g. if it's set twice.
+    pub fn set_log_flag(&mut self, name: &str, enabled: bool) -> Result<()> {
+        let mut expected = self.parse()?.log_flags;
+        expected.set(name, enabled)?;
+
+        let element = format!("<{name}>{}</{name}>", if enabled { 1 } else { 0 });
+        let xml = &self.0;
+        let edited = match find_element(xml, "log_flags")? {
+            Some(Element { content: Some(flags), .. }) => {
+                let flag = match find_element(&xml[flags.clone()], name)? {
+                    Some(Element { range, .. }) => (flags.start + range.start)..(flags.start + range.end),
+                    None => flags.end..flags.end,
+                };
+                replace_range(xml, flag, &element)
+            }
+            Some(Element { range, content: None }) => {
+                replace_range(xml, range, &format!("<log_flags>{element}</log_flags>"))
+            }
+            None => match find_element(xml, "cc_config")? {
+                Some(Element { content: Some(root), .. }) => {
+                    replace_range(xml, root.start..root.start, &format!("\n<log_flags>{element}</log_flags>"))
+                }
+                Some(Element { range, content: None }) => {
+                    replace_range(xml, range, &format!("<cc_config><log_flags>{element}</log_flags></cc_config>"))
+                }
+                None => return Err(unsupported_cc_config("the root isn't <cc_config>")),
+            },
+        };
+
+        // the edit mustn't change any flag but the given one
+        let edited = Self::new(edited)?;
+        if edited.parse()?.log_flags != expected {
+            return Err(unsupported_cc_config("the log flags are malformed"));
+        }
+        *self = edited;
+        Ok(())
+    }
+}
+
+// An element's position in a document, the content is None for an empty element, e.g. <log_flags/>.
+struct Element {
+    range: Range<usize>,
+    content: Option<Range<usize>>,
+}
+
+// The only element of the given name, elements in several places can't be edited safely.
+fn find_element(xml: &str, name: &str) -> Result<Option<Element>> {
+    let (open, close, empty) = (format!("<{name}>"), format!("</{name}>"), format!("<{name}/>"));
+    match (find_unique(xml, &open)?, find_unique(xml, &close)?, find_unique(xml, &empty)?) {
+        (Some(start), Some(end), None) if start < end => Ok(Some(Element {
+            range: start..(end + close.len()),
+            content: Some((start + open.len())..end),
+        })),
+        (None, None, Some(start)) => Ok(Some(Element { range: start..(start + empty.len()), content: None })),
+        (None, None, None) => Ok(None),
+        _ => Err(unsupported_cc_config(&format!("<{name}> is malformed"))),
+    }
+}
+
+fn find_unique(xml: &str, pattern: &str) -> Result<Option<usize>> {
+    match xml.match_indices(pattern).count() {
+        0 | 1 => Ok(xml.find(pattern)),
+        _ => Err(unsupported_cc_config(&format!("{pattern} occurs several times"))),
+    }
+}
+
+fn replace_range(xml: &str, range: Range<usize>, replacement: &str) -> String {
+    let mut edited = xml.to_string();
+    edited.replace_range(range, replacement);
+    edited
+}
+
+fn unsupported_cc_config(reason: &str) -> Error {
+    Error::InvalidArgument(format!("cc_config.xml can't be changed without losing settings, {reason}"))
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 pub struct CCStatus {
@@ -55,6 +223,13 @@ pub struct CCStatus {
     pub network_status: NetworkStatus,
 }
 
+impl CCStatus {
+    /// Whether the client is allowed to compute, i.e. the tasks aren't suspended.
+    pub fn is_computing(&self) -> bool {
+        self.task_suspend_reason == SuspendReason::NotSuspended
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 pub struct CoprocAMD {
@@ -74,6 +249,27 @@ pub struct CoprocAMD {
     pub opencl: Option<CoprocOpenCL>,
 }
 
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct CoprocNvidia {
+    pub have_cuda: Bool,
+    pub have_opencl: Bool,
+
+    pub count: i32,
+    #[serde(rename="cudaVersion")]
+    pub cuda_version: i32,
+    #[serde(rename="drvVersion")]
+    pub driver_version: i32,
+
+    pub available_ram: f64,
+    pub peak_flops: f64,
+
+    pub name: String,
+
+    #[serde(rename="coproc_opencl")]
+    pub opencl: Option<CoprocOpenCL>,
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 pub struct CoprocOpenCL {
@@ -82,6 +278,9 @@ pub struct CoprocOpenCL {
     pub opencl_driver_version: String,
 
     pub global_mem_size: f64,
+
+    // links the OpenCL view of a GPU to its CUDA or CAL view
+    pub opencl_device_index: Option<i32>,
 }
 
 
@@ -90,6 +289,30 @@ pub struct CoprocOpenCL {
 pub struct Coprocs {
     #[serde(rename="coproc_ati")]
     pub amds: Vec<CoprocAMD>,
+    #[serde(rename="coproc_cuda")]
+    pub nvidias: Vec<CoprocNvidia>,
+}
+
+impl Coprocs {
+    /// The number of GPUs, counting the ones reported under several APIs once,
+    /// i.e. the ones whose OpenCL views share an OpenCL device index.
+    pub fn distinct_gpu_count(&self) -> usize {
+        let amds = self.amds.iter().map(|amd| ("amd", amd.count, amd.opencl.as_ref()));
+        let nvidias = self.nvidias.iter().map(|nvidia| ("nvidia", nvidia.count, nvidia.opencl.as_ref()));
+
+        let mut indexes = std::collections::HashSet::new();
+        let mut count = 0;
+        for (vendor, n, opencl) in amds.chain(nvidias) {
+            let seen = match opencl.and_then(|opencl| opencl.opencl_device_index) {
+                Some(index) => !indexes.insert((vendor, index)),
+                None => false,
+            };
+            if !seen {
+                count += n.max(0) as usize;
+            }
+        }
+        count
+    }
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -110,11 +333,12 @@ pub struct DiskUsageSummary {
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 pub struct DiskUsageSummaryProject {
+    pub disk_share: f64,
     pub disk_usage: f64,
     pub master_url: String,
 }
 
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+#[derive(Copy, Clone, Debug, Default, Deserialize, Serialize)]
 #[serde(default)]
 #[serde(transparent)]
 pub struct Duration(pub f64); // in seconds
@@ -125,6 +349,17 @@ impl From<Duration> for f64 {
     }
 }
 
+// Excludes GPUs from being used by a project, all of them if no device is given.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default, rename = "exclude_gpu")]
+pub struct ExcludeGpu {
+    pub url: String,
+    pub device_num: Option<i32>,
+    #[serde(rename = "type")]
+    pub gpu_type: Option<String>,
+    pub app: Option<String>,
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 pub struct FileTransfer {
@@ -135,7 +370,7 @@ pub struct FileTransfer {
     pub nbytes: f64,
     pub max_nbytes: f64,
 
-    pub status: i32,
+    pub status: FileTransferStatus,
 
     pub file_xfer: Option<FileXfer>,
     pub persistent_file_xfer: Option<PersistentFileXfer>,
@@ -145,6 +380,50 @@ pub struct FileTransfer {
     pub project_url: String,
 }
 
+impl FileTransfer {
+    /// Fraction of the file transferred so far, if the file size is known.
+    pub fn fraction_done(&self) -> Option<f64> {
+        let bytes_xferred = match (&self.file_xfer, &self.persistent_file_xfer) {
+            (Some(xfer), _) => xfer.bytes_xferred,
+            (None, Some(pfx)) => pfx.last_bytes_xferred,
+            (None, None) => 0.,
+        };
+
+        Some(self.nbytes)
+            .filter(|&nbytes| nbytes > 0.)
+            .map(|nbytes| (bytes_xferred / nbytes).clamp(0., 1.))
+    }
+
+    /// Whether the transfer seems to be stuck, i.e. it's retried, backed off or active without progress.
+    pub fn is_stalled(&self) -> bool {
+        let retried = self.persistent_file_xfer.as_ref().is_some_and(|pfx| pfx.num_retries > 0);
+        let without_progress = self.file_xfer.as_ref().is_some_and(|xfer| xfer.xfer_speed == 0.);
+        retried || self.project_backoff.0 > 0. || without_progress
+    }
+}
+
+// BOINC's error code of the last transfer attempt
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct FileTransferStatus(pub i32);
+
+impl Display for FileTransferStatus {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self.0 {
+            0 => return formatter.write_str("ok"),
+            -114 => "download gave up",
+            -115 => "upload gave up",
+            -119 => "MD5 checksum failed",
+            -120 => "signature check failed",
+            -161 => "file not found",
+            -184 => "transient HTTP error",
+            -224 => "permanent HTTP error",
+            code => return write!(formatter, "error {}", code),
+        };
+        write!(formatter, "{} ({})", description, self.0)
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 pub struct FileXfer {
@@ -157,7 +436,15 @@ pub struct FileXfer {
     pub url: String,
 }
 
-#[derive(Debug, Default, Deserialize)]
+// The resources a GPU app version of an app_config.xml's <app> reserves per task
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct GpuVersions {
+    pub cpu_usage: f64,
+    pub gpu_usage: f64,
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(default)]
 pub struct GuiUrl {
     pub name: String,
@@ -165,19 +452,45 @@ pub struct GuiUrl {
     pub url: String,
 }
 
-#[derive(Debug, Default, Deserialize)]
-#[serde(from = "GuiUrlsDto")]
+#[derive(Debug, Default, Serialize)]
 pub struct GuiUrls(pub Vec<GuiUrl>);
 
-#[derive(Debug, Default, Deserialize)]
-#[serde(default)]
-struct GuiUrlsDto {
-    gui_url: Vec<GuiUrl>,
+// The urls may be wrapped in tags like <ifteam>, so any other element is searched
+// for urls as well, in document order, instead of relying on the wrappers being stripped.
+impl<'de> Deserialize<'de> for GuiUrls {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(GuiUrlsVisitor)
+    }
 }
 
-impl From<GuiUrlsDto> for GuiUrls {
-    fn from(dto: GuiUrlsDto) -> Self {
-        GuiUrls ( dto.gui_url )
+struct GuiUrlsVisitor;
+
+impl<'de> Visitor<'de> for GuiUrlsVisitor {
+    type Value = GuiUrls;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("gui_url elements, possibly wrapped")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> std::result::Result<GuiUrls, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut urls = vec![];
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "gui_url" => urls.push(map.next_value()?),
+                // attributes and text
+                _ if key.starts_with(['@', '$']) => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+                _ => urls.extend(map.next_value::<GuiUrls>()?.0),
+            }
+        }
+        Ok(GuiUrls(urls))
     }
 }
 
@@ -213,6 +526,132 @@ pub struct HostInfo {
     pub coprocs: Coprocs,
 }
 
+impl HostInfo {
+    /// The measured floating point speed of a single CPU in GFLOPS.
+    pub fn cpu_gflops(&self) -> f64 {
+        self.p_fpops / 1e9
+    }
+
+    /// The measured integer speed of a single CPU in GIOPS.
+    pub fn cpu_giops(&self) -> f64 {
+        self.p_iops / 1e9
+    }
+
+    pub fn memory_gb(&self) -> f64 {
+        self.m_nbytes / 1e9
+    }
+
+    pub fn swap_gb(&self) -> f64 {
+        self.m_swap / 1e9
+    }
+
+    pub fn disk_total_gb(&self) -> f64 {
+        self.d_total / 1e9
+    }
+
+    pub fn disk_free_gb(&self) -> f64 {
+        self.d_free / 1e9
+    }
+
+    /// The CPU's feature flags, e.g. `["sse2", "avx", "avx2"]`.
+    pub fn features(&self) -> Vec<&str> {
+        self.p_features.split_whitespace().collect()
+    }
+
+    /// Whether the CPU has the feature flag, e.g. `avx2` required by some app versions.
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.p_features.split_whitespace().any(|f| f == feature)
+    }
+}
+
+/// The event log flags of the client's cc_config.xml, i.e. what the client writes to its event log.
+/// Like in the client, task, file_xfer and sched_ops are enabled unless set otherwise.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(default, rename = "log_flags")]
+pub struct LogFlags {
+    pub task: Bool,
+    pub file_xfer: Bool,
+    pub sched_ops: Bool,
+
+    pub checkpoint_debug: Bool,
+    pub coproc_debug: Bool,
+    pub cpu_sched: Bool,
+    pub cpu_sched_debug: Bool,
+    pub file_xfer_debug: Bool,
+    pub http_debug: Bool,
+    pub mem_usage_debug: Bool,
+    pub network_status_debug: Bool,
+    pub rr_simulation: Bool,
+    pub sched_op_debug: Bool,
+    pub task_debug: Bool,
+    pub work_fetch_debug: Bool,
+}
+
+impl Default for LogFlags {
+    fn default() -> Self {
+        Self {
+            task: Bool::True,
+            file_xfer: Bool::True,
+            sched_ops: Bool::True,
+            checkpoint_debug: Bool::False,
+            coproc_debug: Bool::False,
+            cpu_sched: Bool::False,
+            cpu_sched_debug: Bool::False,
+            file_xfer_debug: Bool::False,
+            http_debug: Bool::False,
+            mem_usage_debug: Bool::False,
+            network_status_debug: Bool::False,
+            rr_simulation: Bool::False,
+            sched_op_debug: Bool::False,
+            task_debug: Bool::False,
+            work_fetch_debug: Bool::False,
+        }
+    }
+}
+
+impl LogFlags {
+    /// The names of the flags and whether they're enabled.
+    pub fn flags(&self) -> Vec<(&'static str, bool)> {
+        self.clone().flags_mut().into_iter().map(|(name, flag)| (name, bool::from(*flag))).collect()
+    }
+
+    /// The names of the enabled flags.
+    pub fn enabled(&self) -> Vec<&'static str> {
+        self.flags().into_iter().filter(|&(_, enabled)| enabled).map(|(name, _)| name).collect()
+    }
+
+    /// Enables or disables the flag of the given name, e.g. `cpu_sched`.
+    pub fn set(&mut self, name: &str, enabled: bool) -> Result<()> {
+        let (_, flag) = self
+            .flags_mut()
+            .into_iter()
+            .find(|(flag_name, _)| *flag_name == name)
+            .ok_or_else(|| Error::InvalidArgument(format!("unknown log flag: {}", name)))?;
+        *flag = enabled.into();
+        Ok(())
+    }
+
+    fn flags_mut(&mut self) -> [(&'static str, &mut Bool); 15] {
+        [
+            ("task", &mut self.task),
+            ("file_xfer", &mut self.file_xfer),
+            ("sched_ops", &mut self.sched_ops),
+            ("checkpoint_debug", &mut self.checkpoint_debug),
+            ("coproc_debug", &mut self.coproc_debug),
+            ("cpu_sched", &mut self.cpu_sched),
+            ("cpu_sched_debug", &mut self.cpu_sched_debug),
+            ("file_xfer_debug", &mut self.file_xfer_debug),
+            ("http_debug", &mut self.http_debug),
+            ("mem_usage_debug", &mut self.mem_usage_debug),
+            ("network_status_debug", &mut self.network_status_debug),
+            ("rr_simulation", &mut self.rr_simulation),
+            ("sched_op_debug", &mut self.sched_op_debug),
+            ("task_debug", &mut self.task_debug),
+            ("work_fetch_debug", &mut self.work_fetch_debug),
+        ]
+    }
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 pub struct Message {
@@ -225,6 +664,75 @@ pub struct Message {
     pub timestamp: Timestamp,
 }
 
+impl Message {
+    /// Recognizes the common messages of the client by their text.
+    pub fn classify(&self) -> MessageKind {
+        let body = self.body.trim();
+        let after = |prefix: &str| body.strip_prefix(prefix).map(|rest| rest.trim().to_string());
+        // the task or file of a message followed by the reason, e.g. "<name>: <reason>"
+        let subject = |rest: String| rest.split(':').next().unwrap_or_default().trim().to_string();
+
+        if body.starts_with("Sending scheduler request") {
+            MessageKind::SchedulerRequest
+        } else if let Some(reason) = after("Scheduler request failed:") {
+            MessageKind::SchedulerRequestFailed(reason)
+        } else if let Some(rest) = after("Scheduler request completed: got") {
+            rest.split_whitespace()
+                .next()
+                .and_then(|n| n.parse().ok())
+                .map_or(MessageKind::Other, MessageKind::SchedulerRequestCompleted)
+        } else if body == "Project has no tasks available" || body == "No tasks sent" {
+            MessageKind::NoTasksAvailable
+        } else if let Some(reason) = after("Not requesting tasks:") {
+            MessageKind::NotRequestingTasks(reason)
+        } else if let Some(task) = after("Starting task") {
+            MessageKind::TaskStarted(task)
+        } else if let Some(task) = body.strip_prefix("Computation for task ").and_then(|rest| rest.strip_suffix(" finished")) {
+            MessageKind::TaskFinished(task.to_string())
+        } else if let Some(rest) = after("Aborting task") {
+            MessageKind::TaskAborted(subject(rest))
+        } else if let Some(rest) = after("Giving up on download of").or_else(|| after("Giving up on upload of")) {
+            MessageKind::TransferFailed(subject(rest))
+        } else if let Some(rest) = after("Temporarily failed download of").or_else(|| after("Temporarily failed upload of")) {
+            MessageKind::TransferFailed(subject(rest))
+        } else {
+            MessageKind::Other
+        }
+    }
+}
+
+/// The meaning of a [Message], see [Message::classify].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MessageKind {
+    /// A scheduler request is sent to the project.
+    SchedulerRequest,
+    /// The scheduler request succeeded and got the given number of new tasks.
+    SchedulerRequestCompleted(u32),
+    /// The scheduler request failed for the given reason, e.g. an HTTP error.
+    SchedulerRequestFailed(String),
+    /// The project has no work for the client.
+    NoTasksAvailable,
+    /// The client doesn't ask for work for the given reason, e.g. a full job cache.
+    NotRequestingTasks(String),
+    TaskStarted(String),
+    TaskFinished(String),
+    TaskAborted(String),
+    /// A download or upload of the given file failed, temporarily or permanently.
+    TransferFailed(String),
+    Other,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct NetStats {
+    pub avg_down: f64,
+    pub avg_time_down: Timestamp,
+    pub avg_time_up: Timestamp,
+    pub avg_up: f64,
+    pub bwdown: f64,
+    pub bwup: f64,
+}
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
 pub struct Notice {
@@ -257,8 +765,72 @@ pub struct PersistentFileXfer {
     pub next_request_time: Timestamp,
 }
 
+/// The platforms a project advertises app versions for, e.g. `windows_x86_64`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(from = "PlatformsDto")]
+pub struct Platforms(pub Vec<String>);
+
 #[derive(Debug, Default, Deserialize)]
 #[serde(default)]
+struct PlatformsDto {
+    name: Vec<String>,
+}
+
+impl From<PlatformsDto> for Platforms {
+    fn from(dto: PlatformsDto) -> Self {
+        Platforms(dto.name)
+    }
+}
+
+/// How a running task progresses compared to the client's estimate, see [Task::progress_health].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProgressHealth {
+    OnTrack,
+    Slow,
+    Stalled,
+}
+
+impl ProgressHealth {
+    // a task without any progress after this many seconds is considered stalled
+    const STALLED_AFTER: f64 = 600.;
+    // ratios of the actual to the expected fraction done
+    const SLOW_BELOW: f64 = 0.75;
+    const STALLED_BELOW: f64 = 0.25;
+
+    /// Classifies the fraction done after `elapsed` seconds against the fraction
+    /// expected by the projection `elapsed + estimated_remaining` of the task's runtime.
+    pub fn classify(fraction_done: f64, elapsed: f64, estimated_remaining: f64) -> Self {
+        if elapsed <= 0. {
+            return ProgressHealth::OnTrack;
+        }
+        if fraction_done <= 0. {
+            return if elapsed >= Self::STALLED_AFTER { ProgressHealth::Stalled } else { ProgressHealth::OnTrack };
+        }
+
+        let expected = elapsed / (elapsed + estimated_remaining.max(0.));
+        let ratio = fraction_done / expected;
+        if ratio < Self::STALLED_BELOW {
+            ProgressHealth::Stalled
+        } else if ratio < Self::SLOW_BELOW {
+            ProgressHealth::Slow
+        } else {
+            ProgressHealth::OnTrack
+        }
+    }
+}
+
+impl Display for ProgressHealth {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str(match self {
+            ProgressHealth::OnTrack => "on track",
+            ProgressHealth::Slow => "slow",
+            ProgressHealth::Stalled => "stalled",
+        })
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
 pub struct Project {
     pub anonymous_platform: Bool,
     pub attached_via_acct_mgr: Bool,
@@ -286,6 +858,8 @@ pub struct Project {
 
     pub gui_urls: GuiUrls,
 
+    pub app_config: ProjectAppConfig,
+
     pub hostid: i32,
     pub master_fetch_failures: i32,
     pub njobs_error: i32,
@@ -307,9 +881,51 @@ pub struct Project {
     pub project_files_downloaded_time: Timestamp,
 }
 
+/// The effective settings of a project's app_config.xml, empty if it has none.
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ProjectAppConfig {
+    pub report_results_immediately: Bool,
+
+    pub project_max_concurrent: i32,
+
+    pub app: Vec<AppConfig>,
+    pub app_version: Vec<AppVersionConfig>,
+}
+
+/// A project of the list of all projects known to BOINC, i.e. one users may attach to.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ProjectListEntry {
+    pub description: String,
+    pub general_area: String,
+    pub home: String,
+    pub image: String,
+    pub name: String,
+    pub specific_area: String,
+    pub summary: String,
+    pub url: String,
+
+    pub platforms: Platforms,
+}
+
+impl ProjectListEntry {
+    /// Whether the project advertises an app version for any of the client's platforms.
+    /// Plan classes like in `x86_64-pc-linux-gnu[vbox]` are ignored.
+    /// Projects advertising no platforms are considered compatible.
+    pub fn is_compatible(&self, platforms: &[String]) -> bool {
+        let advertised = &self.platforms.0;
+        advertised.is_empty()
+            || advertised
+                .iter()
+                .map(|platform| platform.split('[').next().unwrap_or_default().trim())
+                .any(|platform| platforms.iter().any(|p| p == platform))
+    }
+}
+
 // We're calling BOINC's 'result' structure 'task' because of
 // the naming clash with Rust's 'Result' type used everywhere.
-#[derive(Debug, Default, Deserialize)]
+#[derive(Debug, Default, Deserialize, Serialize)]
 #[serde(default)]
 pub struct Task {
     pub coproc_missing: Bool,
@@ -345,18 +961,124 @@ pub struct Task {
     pub report_deadline: Timestamp,
 }
 
-#[derive(Copy, Clone, Debug, Default, Deserialize)]
+impl Task {
+    /// Estimated seconds until the task completes, None if it isn't started or already computed.
+    ///
+    /// Extrapolates the elapsed time by the fraction done if both are known,
+    /// otherwise falls back to the client's estimate of the remaining CPU time.
+    pub fn estimated_time_remaining(&self) -> Option<f64> {
+        if self.state > ResultClientState::FilesDownloaded {
+            return None;
+        }
+        let active_task = self.active_task.as_ref()?;
+        let fraction_done = active_task.fraction_done;
+        if fraction_done >= 1. {
+            return None;
+        }
+
+        let elapsed = active_task.elapsed_time.0;
+        if fraction_done > 0. && elapsed > 0. {
+            return Some(elapsed * (1. - fraction_done) / fraction_done);
+        }
+        Some(self.estimated_cpu_time_remaining.0).filter(|&remaining| remaining > 0.)
+    }
+
+    /// Estimated completion time of the task, see [Task::estimated_time_remaining].
+    pub fn estimated_completion(&self, now: Timestamp) -> Option<Timestamp> {
+        self.estimated_time_remaining().map(|remaining| Timestamp(now.0 + remaining))
+    }
+
+    /// Progress of the task compared to its estimate, None if it isn't running
+    /// or the client didn't report a valid fraction done.
+    pub fn progress_health(&self) -> Option<ProgressHealth> {
+        if self.state != ResultClientState::FilesDownloaded {
+            return None;
+        }
+        let active_task = self.active_task.as_ref()?;
+        if active_task.scheduler_state != SchedulerState::Scheduled || !(0. ..1.).contains(&active_task.fraction_done) {
+            return None;
+        }
+        Some(ProgressHealth::classify(
+            active_task.fraction_done,
+            active_task.elapsed_time.0,
+            self.estimated_cpu_time_remaining.0,
+        ))
+    }
+}
+
+/// The tasks of a client grouped by their lifecycle, see [TaskGroups::from].
+#[derive(Debug, Default)]
+pub struct TaskGroups {
+    /// Tasks still in progress, i.e. waiting to run, running or uploading.
+    pub active: Vec<Task>,
+    /// Successfully computed and uploaded tasks waiting to be reported to their project.
+    pub waiting_to_report: Vec<Task>,
+    /// Tasks failed to compute or upload or which were aborted, reported or not.
+    pub errored: Vec<Task>,
+}
+
+impl From<Vec<Task>> for TaskGroups {
+    fn from(tasks: Vec<Task>) -> Self {
+        let mut groups = TaskGroups::default();
+        for task in tasks {
+            let errored = matches!(task.state,
+                ResultClientState::ComputeError | ResultClientState::Aborted | ResultClientState::UploadFailed);
+            if errored {
+                groups.errored.push(task);
+            } else if bool::from(task.ready_to_report) && task.active_task.is_none() {
+                groups.waiting_to_report.push(task);
+            } else {
+                groups.active.push(task);
+            }
+        }
+        groups
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct TimeStats {
+    pub active_frac: f64,
+    pub connected_frac: f64,
+    pub cpu_and_network_available_frac: f64,
+    pub gpu_active_frac: f64,
+    pub on_frac: f64,
+
+    pub previous_uptime: Duration,
+    pub session_active_duration: Duration,
+    pub session_gpu_active_duration: Duration,
+    pub total_active_duration: Duration,
+    pub total_duration: Duration,
+    pub total_gpu_active_duration: Duration,
+
+    pub client_start_time: Timestamp,
+    pub total_start_time: Timestamp,
+}
+
+#[derive(Copy, Clone, Debug, Default, Deserialize, PartialEq, PartialOrd, Serialize)]
 #[serde(default)]
 #[serde(transparent)]
 pub struct Timestamp(pub f64); // seconds since epoch in UTC
 
+impl Timestamp {
+    /// Seconds elapsed since the timestamp, None if it's unset.
+    /// Timestamps in the future have an age of zero.
+    pub fn age(&self, now: f64) -> Option<f64> {
+        if self.0 <= 0. {
+            return None;
+        }
+        Some((now - self.0).max(0.))
+    }
+}
+
 impl From<Timestamp> for f64 {
     fn from(t: Timestamp) -> Self {
         t.0
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+// the field order matters for the derived ordering
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
 #[serde(rename = "version")]
 pub struct Version {
     pub major: i32,
@@ -374,8 +1096,471 @@ impl std::default::Default for Version {
     }
 }
 
+impl Version {
+    pub fn new(major: i32, minor: i32, release: i32) -> Self {
+        Self { major, minor, release }
+    }
+
+    /// Fails with a client error if this version is older than the required one.
+    pub fn requires_at_least(&self, required: Version) -> Result<()> {
+        if *self < required {
+            Err(Error::Client(format!("requires client >= {}", required)))
+        } else {
+            Ok(())
+        }
+    }
+}
+
 impl Display for Version {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         formatter.write_str(&format!("{}.{}.{}", self.major, self.minor, self.release))
     }
 }
+
+// ----- Tests -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libjoincserde::from_str;
+
+    #[test]
+    fn deserializes_wrapped_gui_urls() {
+        let xml = "\
+            <project>
+                <master_url>http://p.org/</master_url>
+                <gui_urls>
+                    <gui_url><name>u1</name></gui_url>
+                    <ifteam>
+                        <gui_url><name>u2</name></gui_url>
+                        <gui_url><name>u3</name></gui_url>
+                    </ifteam>
+                    <gui_url><name>u4</name></gui_url>
+                </gui_urls>
+            </project>";
+        let deserialized: Project = from_str(xml).unwrap();
+
+        let names: Vec<&str> = deserialized.gui_urls.0.iter().map(|url| url.name.as_str()).collect();
+        assert_eq!(names, ["u1", "u2", "u3", "u4"]);
+        assert_eq!(deserialized.master_url, "http://p.org/");
+
+        let empty: Project = from_str("<project><gui_urls/></project>").unwrap();
+        assert!(empty.gui_urls.0.is_empty());
+    }
+
+    #[test]
+    fn deserializes_log_flags() {
+        let xml = "\
+            <cc_config>
+                <log_flags>
+                    <file_xfer>0</file_xfer>
+                    <cpu_sched>1</cpu_sched>
+                    <work_fetch_debug/>
+                    <unmodeled_debug>1</unmodeled_debug>
+                </log_flags>
+                <options>
+                    <ncpus>2</ncpus>
+                </options>
+            </cc_config>";
+        let deserialized: CcConfigFile = from_str(xml).unwrap();
+
+        assert_eq!(deserialized.log_flags.enabled(), ["task", "sched_ops", "cpu_sched", "work_fetch_debug"]);
+        assert_eq!(deserialized.options.ncpus, Some(2));
+
+        let defaults: CcConfigFile = from_str("<cc_config/>").unwrap();
+        assert_eq!(defaults.log_flags, LogFlags::default());
+    }
+
+    #[test]
+    fn sets_log_flag_of_raw_cc_config_in_place() {
+        let xml = "\
+<cc_config>
+    <log_flags>
+        <task>1</task>
+        <statefile_debug>1</statefile_debug>
+    </log_flags>
+    <options>
+        <use_all_gpus>1</use_all_gpus>
+    </options>
+</cc_config>";
+        let mut raw = RawCcConfig::new(xml.to_string()).unwrap();
+        raw.set_log_flag("task", false).unwrap();
+        raw.set_log_flag("cpu_sched", true).unwrap();
+
+        assert_eq!(raw.as_str(), xml.replace("<task>1</task>", "<task>0</task>")
+            .replace("</statefile_debug>\n    </log_flags>", "</statefile_debug>\n    <cpu_sched>1</cpu_sched></log_flags>"));
+        assert_eq!(raw.parse().unwrap().log_flags.enabled(), ["file_xfer", "sched_ops", "cpu_sched"]);
+    }
+
+    #[test]
+    fn adds_log_flags_to_raw_cc_config() {
+        let mut raw = RawCcConfig::new("<cc_config><options/></cc_config>".to_string()).unwrap();
+        raw.set_log_flag("http_debug", true).unwrap();
+        assert_eq!(raw.as_str(), "<cc_config>\n<log_flags><http_debug>1</http_debug></log_flags><options/></cc_config>");
+
+        let mut raw = RawCcConfig::new("<cc_config><log_flags/></cc_config>".to_string()).unwrap();
+        raw.set_log_flag("task", false).unwrap();
+        assert_eq!(raw.as_str(), "<cc_config><log_flags><task>0</task></log_flags></cc_config>");
+    }
+
+    #[test]
+    fn refuses_ambiguous_raw_cc_config() {
+        let xml = "<cc_config><log_flags><task>1</task><task>0</task></log_flags></cc_config>";
+        let mut raw = RawCcConfig(xml.to_string());
+        assert!(raw.set_log_flag("task", false).is_err());
+        assert_eq!(raw.as_str(), xml);
+
+        let xml = "<cc_config><log_flags><task>1</task></log_flags><!-- <log_flags/> --></cc_config>";
+        let mut raw = RawCcConfig::new(xml.to_string()).unwrap();
+        assert!(matches!(raw.set_log_flag("task", false), Err(Error::InvalidArgument(_))));
+        assert_eq!(raw.as_str(), xml);
+
+        let mut raw = RawCcConfig::new("<cc_config/>".to_string()).unwrap();
+        assert!(matches!(raw.set_log_flag("nonsense", true), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn sets_log_flags_by_name() {
+        let mut flags = LogFlags::default();
+        flags.set("task", false).unwrap();
+        flags.set("http_debug", true).unwrap();
+
+        assert_eq!(flags.enabled(), ["file_xfer", "sched_ops", "http_debug"]);
+        assert!(matches!(flags.set("nonsense", true), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn counts_gpus_reported_under_several_apis_once() {
+        let xml = "\
+            <coprocs>
+                <coproc_cuda>
+                    <count>1</count>
+                    <name>NVIDIA GeForce RTX 3060</name>
+                    <have_cuda>1</have_cuda>
+                    <cudaVersion>12020</cudaVersion>
+                    <coproc_opencl><name>NVIDIA GeForce RTX 3060</name><opencl_device_index>0</opencl_device_index></coproc_opencl>
+                </coproc_cuda>
+                <coproc_cuda>
+                    <count>1</count>
+                    <name>NVIDIA GeForce RTX 3060</name>
+                    <have_opencl>1</have_opencl>
+                    <coproc_opencl><name>NVIDIA GeForce RTX 3060</name><opencl_device_index>0</opencl_device_index></coproc_opencl>
+                </coproc_cuda>
+                <coproc_ati>
+                    <count>2</count>
+                    <name>Radeon</name>
+                </coproc_ati>
+                <coproc_ati>
+                    <count>1</count>
+                    <coproc_opencl><name>Radeon</name><opencl_device_index>0</opencl_device_index></coproc_opencl>
+                </coproc_ati>
+            </coprocs>";
+        let coprocs: Coprocs = from_str(xml).unwrap();
+
+        assert_eq!(coprocs.nvidias.len(), 2);
+        assert_eq!(coprocs.nvidias[0].cuda_version, 12020);
+        assert_eq!(coprocs.nvidias[1].opencl.as_ref().unwrap().opencl_device_index, Some(0));
+        assert_eq!(coprocs.distinct_gpu_count(), 4);
+        assert_eq!(Coprocs::default().distinct_gpu_count(), 0);
+    }
+
+    #[test]
+    fn counts_all_gpus_of_a_coproc_with_opencl_device_index() {
+        let xml = "\
+            <coprocs>
+                <coproc_cuda>
+                    <count>2</count>
+                    <have_cuda>1</have_cuda>
+                    <coproc_opencl><opencl_device_index>0</opencl_device_index></coproc_opencl>
+                </coproc_cuda>
+                <coproc_cuda>
+                    <count>2</count>
+                    <have_opencl>1</have_opencl>
+                    <coproc_opencl><opencl_device_index>0</opencl_device_index></coproc_opencl>
+                </coproc_cuda>
+                <coproc_ati>
+                    <count>3</count>
+                    <coproc_opencl><opencl_device_index>0</opencl_device_index></coproc_opencl>
+                </coproc_ati>
+            </coprocs>";
+        let coprocs: Coprocs = from_str(xml).unwrap();
+
+        assert_eq!(coprocs.distinct_gpu_count(), 5);
+    }
+
+    #[test]
+    fn deserializes_disk_usage_summary() {
+        let xml = "\
+            <disk_usage_summary>
+                <project>
+                    <master_url>http://p.org/</master_url>
+                    <disk_usage>1024</disk_usage>
+                    <disk_share>4096</disk_share>
+                </project>
+                <d_total>100000</d_total>
+                <d_free>50000</d_free>
+                <d_boinc>2048</d_boinc>
+                <d_allowed>30000</d_allowed>
+            </disk_usage_summary>";
+        let deserialized: DiskUsageSummary = from_str(xml).unwrap();
+
+        assert_eq!(deserialized.total, 100000.);
+        assert_eq!(deserialized.free, 50000.);
+        assert_eq!(deserialized.boinc, 2048.);
+        assert_eq!(deserialized.allowed, 30000.);
+        assert_eq!(deserialized.projects.len(), 1);
+        assert_eq!(deserialized.projects[0].master_url, "http://p.org/");
+        assert_eq!(deserialized.projects[0].disk_usage, 1024.);
+        assert_eq!(deserialized.projects[0].disk_share, 4096.);
+    }
+
+    #[test]
+    fn orders_versions() {
+        assert!(Version::new(7, 22, 0) < Version::new(7, 22, 1));
+        assert!(Version::new(7, 22, 9) < Version::new(7, 24, 0));
+        assert!(Version::new(7, 24, 0) < Version::new(8, 0, 0));
+        assert_eq!(Version::new(7, 24, 1), Version::new(7, 24, 1));
+    }
+
+    #[test]
+    fn gates_by_client_version() {
+        let client = Version::new(7, 22, 2);
+
+        assert!(client.requires_at_least(Version::new(7, 20, 0)).is_ok());
+        assert!(client.requires_at_least(Version::new(7, 22, 2)).is_ok());
+        assert!(matches!(client.requires_at_least(Version::new(7, 24, 0)),
+            Err(Error::Client(msg)) if msg == "requires client >= 7.24.0"));
+    }
+
+    #[test]
+    fn converts_host_info_units() {
+        let host_info = HostInfo {
+            p_fpops: 4.5e9,
+            p_iops: 1.25e10,
+            m_nbytes: 16e9,
+            m_swap: 2e9,
+            d_total: 512e9,
+            d_free: 128e9,
+            ..Default::default()
+        };
+
+        assert_eq!(host_info.cpu_gflops(), 4.5);
+        assert_eq!(host_info.cpu_giops(), 12.5);
+        assert_eq!(host_info.memory_gb(), 16.);
+        assert_eq!(host_info.swap_gb(), 2.);
+        assert_eq!(host_info.disk_total_gb(), 512.);
+        assert_eq!(host_info.disk_free_gb(), 128.);
+    }
+
+    #[test]
+    fn splits_cpu_features() {
+        let host_info = HostInfo { p_features: " sse2 avx\n avx2 ".to_string(), ..Default::default() };
+
+        assert_eq!(host_info.features(), ["sse2", "avx", "avx2"]);
+        assert!(host_info.has_feature("avx2"));
+        assert!(!host_info.has_feature("avx512f"));
+        assert!(!host_info.has_feature("av"));
+        assert!(HostInfo::default().features().is_empty());
+    }
+
+    #[test]
+    fn checks_project_platforms() {
+        let project = |platforms: &[&str]| ProjectListEntry {
+            platforms: Platforms(platforms.iter().map(|p| p.to_string()).collect()),
+            ..Default::default()
+        };
+        let client = ["x86_64-pc-linux-gnu".to_string(), "i686-pc-linux-gnu".to_string()];
+
+        assert!(project(&["windows_x86_64", "i686-pc-linux-gnu"]).is_compatible(&client));
+        assert!(project(&["x86_64-pc-linux-gnu[vbox64]"]).is_compatible(&client));
+        assert!(!project(&["windows_x86_64", "x86_64-apple-darwin"]).is_compatible(&client));
+        assert!(project(&[]).is_compatible(&client));
+        assert!(!project(&["windows_x86_64"]).is_compatible(&[]));
+    }
+
+    #[test]
+    fn estimates_task_completion() {
+        let task = |fraction_done, elapsed_time, estimated_cpu_time_remaining| Task {
+            state: ResultClientState::FilesDownloaded,
+            estimated_cpu_time_remaining: Duration(estimated_cpu_time_remaining),
+            active_task: Some(ActiveTask {
+                fraction_done,
+                elapsed_time: Duration(elapsed_time),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(task(0.25, 600., 5000.).estimated_time_remaining(), Some(1800.));
+        assert_eq!(task(0., 0., 5000.).estimated_time_remaining(), Some(5000.));
+        assert_eq!(task(0.5, 0., 100.).estimated_time_remaining(), Some(100.));
+        assert_eq!(task(0., 0., 0.).estimated_time_remaining(), None);
+        assert_eq!(task(1., 600., 0.).estimated_time_remaining(), None);
+        assert_eq!(task(0.5, 60., 0.).estimated_completion(Timestamp(1000.)).map(f64::from), Some(1060.));
+
+        let not_started = Task { estimated_cpu_time_remaining: Duration(5000.), ..Default::default() };
+        assert_eq!(not_started.estimated_time_remaining(), None);
+
+        let computed = Task { state: ResultClientState::FilesUploaded, ..task(0.5, 60., 0.) };
+        assert_eq!(computed.estimated_time_remaining(), None);
+    }
+
+    #[test]
+    fn classifies_messages() {
+        let kind = |body: &str| Message { body: body.to_string(), ..Default::default() }.classify();
+
+        assert_eq!(kind("Sending scheduler request: To fetch work."), MessageKind::SchedulerRequest);
+        assert_eq!(kind("Sending scheduler request: Requested by user."), MessageKind::SchedulerRequest);
+        assert_eq!(kind("Scheduler request completed: got 3 new tasks"), MessageKind::SchedulerRequestCompleted(3));
+        assert_eq!(kind("Scheduler request completed: got 0 new tasks"), MessageKind::SchedulerRequestCompleted(0));
+        assert_eq!(kind("Scheduler request failed: HTTP internal server error"),
+            MessageKind::SchedulerRequestFailed("HTTP internal server error".to_string()));
+        assert_eq!(kind("Scheduler request failed: Couldn't connect to server"),
+            MessageKind::SchedulerRequestFailed("Couldn't connect to server".to_string()));
+        assert_eq!(kind("Project has no tasks available"), MessageKind::NoTasksAvailable);
+        assert_eq!(kind("No tasks sent\n"), MessageKind::NoTasksAvailable);
+        assert_eq!(kind("Not requesting tasks: don't need (CPU: job cache full; NVIDIA GPU: )"),
+            MessageKind::NotRequestingTasks("don't need (CPU: job cache full; NVIDIA GPU: )".to_string()));
+        assert_eq!(kind("Starting task h1_0680.80_O3aC01Cl1In0__O3ASHF1d_681.00Hz_33301_0"),
+            MessageKind::TaskStarted("h1_0680.80_O3aC01Cl1In0__O3ASHF1d_681.00Hz_33301_0".to_string()));
+        assert_eq!(kind("Computation for task wcg_MCM1_0201234_5678_0 finished"),
+            MessageKind::TaskFinished("wcg_MCM1_0201234_5678_0".to_string()));
+        assert_eq!(kind("Aborting task 20ap06aa.1234.5678.8.15.10_0: exceeded elapsed time limit 34567.89 (180000.00G/5.21G)"),
+            MessageKind::TaskAborted("20ap06aa.1234.5678.8.15.10_0".to_string()));
+        assert_eq!(kind("Giving up on download of LATeah3012L00.dat: permanent HTTP error"),
+            MessageKind::TransferFailed("LATeah3012L00.dat".to_string()));
+        assert_eq!(kind("Temporarily failed upload of wcg_MCM1_0201234_5678_0_r12345_0: transient HTTP error"),
+            MessageKind::TransferFailed("wcg_MCM1_0201234_5678_0_r12345_0".to_string()));
+        assert_eq!(kind("Started download of LATeah3012L00.dat"), MessageKind::Other);
+        assert_eq!(kind("Scheduler request completed: got some tasks"), MessageKind::Other);
+        assert_eq!(kind(""), MessageKind::Other);
+    }
+
+    #[test]
+    fn groups_tasks_by_lifecycle() {
+        let task = |name: &str, state, ready_to_report: bool, running: bool| Task {
+            name: name.to_string(),
+            state,
+            ready_to_report: ready_to_report.into(),
+            active_task: Some(ActiveTask::default()).filter(|_| running),
+            ..Default::default()
+        };
+        let names = |tasks: &[Task]| tasks.iter().map(|task| task.name.clone()).collect::<Vec<_>>();
+
+        let groups = TaskGroups::from(vec![
+            task("queued", ResultClientState::FilesDownloaded, false, false),
+            task("running", ResultClientState::FilesDownloaded, false, true),
+            task("uploading", ResultClientState::FilesUploading, false, false),
+            task("done", ResultClientState::FilesUploaded, true, false),
+            task("failed", ResultClientState::ComputeError, true, false),
+            task("aborted", ResultClientState::Aborted, false, false),
+            task("upload failed", ResultClientState::UploadFailed, true, false),
+        ]);
+
+        assert_eq!(names(&groups.active), ["queued", "running", "uploading"]);
+        assert_eq!(names(&groups.waiting_to_report), ["done"]);
+        assert_eq!(names(&groups.errored), ["failed", "aborted", "upload failed"]);
+        assert!(TaskGroups::from(vec![]).active.is_empty());
+    }
+
+    #[test]
+    fn orders_timestamps() {
+        let mut timestamps = vec![Timestamp(300.), Timestamp(100.), Timestamp(200.)];
+        timestamps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        assert_eq!(timestamps, [Timestamp(100.), Timestamp(200.), Timestamp(300.)]);
+        assert!(Timestamp(1.) > Timestamp::default());
+    }
+
+    #[test]
+    fn computes_age_of_timestamps() {
+        assert_eq!(Timestamp(1000.).age(1300.), Some(300.));
+        assert_eq!(Timestamp(1000.).age(1000.), Some(0.));
+        assert_eq!(Timestamp(1000.).age(900.), Some(0.));
+        assert_eq!(Timestamp(0.).age(1300.), None);
+        assert_eq!(Timestamp::default().age(1300.), None);
+    }
+
+    #[test]
+    fn classifies_progress_health() {
+        assert_eq!(ProgressHealth::classify(0.5, 600., 600.), ProgressHealth::OnTrack);
+        assert_eq!(ProgressHealth::classify(0.375, 600., 600.), ProgressHealth::OnTrack);
+        assert_eq!(ProgressHealth::classify(0.37, 600., 600.), ProgressHealth::Slow);
+        assert_eq!(ProgressHealth::classify(0.125, 600., 600.), ProgressHealth::Slow);
+        assert_eq!(ProgressHealth::classify(0.12, 600., 600.), ProgressHealth::Stalled);
+        assert_eq!(ProgressHealth::classify(0.5, 3600., 0.), ProgressHealth::Slow);
+        assert_eq!(ProgressHealth::classify(0., 599., 600.), ProgressHealth::OnTrack);
+        assert_eq!(ProgressHealth::classify(0., 600., 600.), ProgressHealth::Stalled);
+        assert_eq!(ProgressHealth::classify(0., 0., 600.), ProgressHealth::OnTrack);
+    }
+
+    #[test]
+    fn gets_progress_health_of_running_tasks() {
+        let task = |scheduler_state, fraction_done| Task {
+            state: ResultClientState::FilesDownloaded,
+            estimated_cpu_time_remaining: Duration(600.),
+            active_task: Some(ActiveTask {
+                scheduler_state,
+                fraction_done,
+                elapsed_time: Duration(600.),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(task(SchedulerState::Scheduled, 0.1).progress_health(), Some(ProgressHealth::Stalled));
+        assert_eq!(task(SchedulerState::Scheduled, 0.5).progress_health(), Some(ProgressHealth::OnTrack));
+        assert_eq!(task(SchedulerState::Preempted, 0.1).progress_health(), None);
+        assert_eq!(task(SchedulerState::Scheduled, -1.).progress_health(), None);
+        assert_eq!(task(SchedulerState::Scheduled, f64::NAN).progress_health(), None);
+        assert_eq!(Task::default().progress_health(), None);
+    }
+
+    #[test]
+    fn computes_file_transfer_progress() {
+        let transfer = |nbytes, bytes_xferred| FileTransfer {
+            nbytes,
+            file_xfer: Some(FileXfer { bytes_xferred, ..Default::default() }),
+            ..Default::default()
+        };
+
+        assert_eq!(transfer(200., 50.).fraction_done(), Some(0.25));
+        assert_eq!(transfer(200., 200.).fraction_done(), Some(1.));
+        assert_eq!(transfer(200., 300.).fraction_done(), Some(1.));
+        assert_eq!(transfer(0., 0.).fraction_done(), None);
+
+        let inactive = FileTransfer {
+            nbytes: 100.,
+            persistent_file_xfer: Some(PersistentFileXfer { last_bytes_xferred: 10., ..Default::default() }),
+            ..Default::default()
+        };
+        assert_eq!(inactive.fraction_done(), Some(0.1));
+    }
+
+    #[test]
+    fn displays_file_transfer_status() {
+        assert_eq!(FileTransferStatus(0).to_string(), "ok");
+        assert_eq!(FileTransferStatus(-184).to_string(), "transient HTTP error (-184)");
+        assert_eq!(FileTransferStatus(-224).to_string(), "permanent HTTP error (-224)");
+        assert_eq!(FileTransferStatus(-999).to_string(), "error -999");
+    }
+
+    #[test]
+    fn detects_stalled_file_transfers() {
+        let active = |xfer_speed| FileTransfer {
+            file_xfer: Some(FileXfer { xfer_speed, ..Default::default() }),
+            ..Default::default()
+        };
+        let retried = FileTransfer {
+            persistent_file_xfer: Some(PersistentFileXfer { num_retries: 2, ..Default::default() }),
+            ..Default::default()
+        };
+        let backed_off = FileTransfer { project_backoff: Duration(60.), ..Default::default() };
+
+        assert!(!FileTransfer::default().is_stalled());
+        assert!(!active(1024.).is_stalled());
+        assert!(active(0.).is_stalled());
+        assert!(retried.is_stalled());
+        assert!(backed_off.is_stalled());
+    }
+}
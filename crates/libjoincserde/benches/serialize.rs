@@ -0,0 +1,55 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde::Serialize;
+
+// shaped like a set_cc_config request excluding many GPUs
+#[derive(Serialize)]
+#[serde(rename = "exclude_gpu")]
+struct ExcludeGpu {
+    url: String,
+    device_num: i32,
+    #[serde(rename = "type")]
+    gpu_type: String,
+    app: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "options")]
+struct Options {
+    report_results_immediately: bool,
+    max_file_xfers: i32,
+    ncpus: i32,
+    exclude_gpu: Vec<ExcludeGpu>,
+}
+
+fn large_request() -> Options {
+    Options {
+        report_results_immediately: true,
+        max_file_xfers: 8,
+        ncpus: 16,
+        exclude_gpu: (0..64)
+            .map(|n| ExcludeGpu {
+                url: format!("https://project{}.example.org/", n),
+                device_num: n % 4,
+                gpu_type: "NVIDIA".to_string(),
+                app: format!("app_{}", n),
+            })
+            .collect(),
+    }
+}
+
+fn serialize_large_request(c: &mut Criterion) {
+    let request = large_request();
+    let size = libjoincserde::to_vec(&request).unwrap().len();
+
+    let mut group = c.benchmark_group("serialize large request");
+    group.bench_function("default capacity", |b| {
+        b.iter(|| libjoincserde::to_vec(black_box(&request)).unwrap())
+    });
+    group.bench_function("hinted capacity", |b| {
+        b.iter(|| libjoincserde::to_vec_with_capacity(size, black_box(&request)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, serialize_large_request);
+criterion_main!(benches);
@@ -1,20 +1,38 @@
 use crate::error::Result;
 use crate::Error;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use std::io::{self, BufRead};
+
+const UTF8_BOM: &str = "\u{feff}";
 
 pub fn from_str<'a, T>(s: &'a str) -> Result<T>
 where
     T: Deserialize<'a>,
 {
+    let s = s.strip_prefix(UTF8_BOM).unwrap_or(s);
     quick_xml::de::from_str(s).map_err(|err| Error::UnexpectedXml(err.to_string()))
 }
 
+pub fn from_reader<R, T>(reader: R) -> Result<T>
+where
+    R: io::Read,
+    T: DeserializeOwned,
+{
+    let mut reader = io::BufReader::new(reader);
+    if reader.fill_buf()?.starts_with(UTF8_BOM.as_bytes()) {
+        reader.consume(UTF8_BOM.len());
+    }
+    quick_xml::de::from_reader(reader).map_err(|err| Error::UnexpectedXml(err.to_string()))
+}
+
 // ----- Tests -----
 
 #[cfg(test)]
 mod tests {
     use serde::Deserialize;
     use serde_repr::Deserialize_repr;
+    use std::io::Cursor;
 
     #[test]
     fn deserializes_structs() {
@@ -40,6 +58,127 @@ mod tests {
         assert_eq!(deserialized, expected);
     }
 
+    #[test]
+    fn skips_byte_order_mark() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Dto {
+            a: u32,
+        }
+
+        let xml = "\u{feff}<dto><a>47</a></dto>";
+        let deserialized: Dto = super::from_str(xml).unwrap();
+        assert_eq!(deserialized, Dto { a: 47 });
+
+        let deserialized: Dto = super::from_reader(Cursor::new(xml.as_bytes())).unwrap();
+        assert_eq!(deserialized, Dto { a: 47 });
+    }
+
+    #[test]
+    fn deserializes_structs_from_reader() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct InnerDTO {
+            b: i32,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct OuterDTO {
+            a: u32,
+            inner: InnerDTO,
+        }
+
+        let expected = OuterDTO {
+            a: 47,
+            inner: InnerDTO { b: -11 },
+        };
+
+        let xml = "<outer><a>47</a><inner><b>-11</b></inner></outer>";
+        let deserialized: OuterDTO = super::from_reader(Cursor::new(xml)).unwrap();
+
+        assert_eq!(deserialized, expected);
+    }
+
+    #[test]
+    fn deserializes_sequences_from_reader() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Msg {
+            id: u32,
+            body: String,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Msgs {
+            msg: Vec<Msg>,
+        }
+
+        let expected = Msgs {
+            msg: vec![
+                Msg {
+                    id: 2,
+                    body: "foo".to_string(),
+                },
+                Msg {
+                    id: 5,
+                    body: "bar".to_string(),
+                },
+            ],
+        };
+
+        let xml = "<msgs><msg><id>2</id><body>foo</body></msg><msg><id>5</id><body>bar</body></msg></msgs>";
+        let deserialized: Msgs = super::from_reader(Cursor::new(xml)).unwrap();
+
+        assert_eq!(deserialized, expected);
+    }
+
+    #[test]
+    fn deserializes_cdata() {
+        use crate::CData;
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Dto {
+            body: CData,
+        }
+
+        let expected = Dto {
+            body: CData("<b>hi</b>".to_string()),
+        };
+
+        let xml = "<dto><body><![CDATA[<b>hi</b>]]></body></dto>";
+        let deserialized: Dto = super::from_str(xml).unwrap();
+
+        assert_eq!(deserialized, expected);
+    }
+
+    #[test]
+    fn deserializes_64bit_integers() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Dto {
+            signed: i64,
+            unsigned: u64,
+        }
+
+        let expected = Dto {
+            signed: -5000000000,
+            unsigned: 5000000000,
+        };
+
+        let xml = "<dto><signed>-5000000000</signed><unsigned>5000000000</unsigned></dto>";
+        let deserialized: Dto = super::from_str(xml).unwrap();
+
+        assert_eq!(deserialized, expected);
+    }
+
+    #[test]
+    fn fails_on_integer_overflow() {
+        #[derive(Deserialize, Debug)]
+        struct Dto {
+            #[serde(rename = "value")]
+            _value: i32,
+        }
+
+        let xml = "<dto><value>5000000000</value></dto>";
+        assert!(super::from_str::<Dto>(xml).is_err());
+    }
+
     #[test]
     fn deserializes_enums() {
         #[derive(Deserialize_repr, Debug, PartialEq)]
@@ -191,4 +330,37 @@ mod tests {
 
         assert_eq!(deserialized, expected);
     }
+
+    #[test]
+    fn deserializes_attributes() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Foo {
+            #[serde(rename = "@bar")]
+            bar: String,
+            #[serde(rename = "$text")]
+            text: String,
+        }
+
+        let deserialized: Foo = super::from_str("<foo bar=\"baz\">x</foo>").unwrap();
+
+        assert_eq!(deserialized, Foo { bar: "baz".to_string(), text: "x".to_string() });
+    }
+
+    #[test]
+    fn ignores_undeclared_attributes() {
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Task {
+            name: String,
+        }
+
+        #[derive(Deserialize, Debug, PartialEq)]
+        struct Tasks {
+            result: Vec<Task>,
+        }
+
+        let xml = "<results><result active=\"1\"><name>a</name></result><result><name>b</name></result></results>";
+        let deserialized: Tasks = super::from_str(xml).unwrap();
+
+        assert_eq!(deserialized.result, vec![Task { name: "a".to_string() }, Task { name: "b".to_string() }]);
+    }
 }
@@ -1,8 +1,116 @@
 mod de;
 mod error;
+mod pretty;
 mod ser;
 
-pub use de::from_str;
+pub use de::{from_reader, from_str};
 pub use error::{Error, Result};
-pub use ser::{to_vec, to_writer, Serializer};
+pub use pretty::pretty_print;
+pub use ser::{to_vec, to_vec_with_capacity, to_writer, CData, Serializer};
 pub use ser::{to_vec_formatted, to_writer_formatted, CompactFormatter, PrettyFormatter};
+
+// ----- Tests -----
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use proptest::test_runner::{Config, RngAlgorithm, TestRng, TestRunner};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "inner")]
+    struct Inner {
+        number: i64,
+        text: String,
+    }
+
+    // structs are tagged by their name, so the items need a type of their own
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "item")]
+    struct Item {
+        text: String,
+    }
+
+    #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename = "outer")]
+    struct Outer {
+        flag: bool,
+        unsigned: u32,
+        real: f64,
+        optional: Option<i32>,
+        inner: Inner,
+        #[serde(default)]
+        item: Vec<Item>,
+        #[serde(default)]
+        numbers: Vec<u16>,
+    }
+
+    // The deserializer trims text, so leading and trailing whitespace doesn't survive a round trip.
+    fn text() -> impl Strategy<Value = String> {
+        "[ -~äöü€]{0,24}".prop_map(|s| s.trim().to_string())
+    }
+
+    fn inner() -> impl Strategy<Value = Inner> {
+        (any::<i64>(), text()).prop_map(|(number, text)| Inner { number, text })
+    }
+
+    fn outer() -> impl Strategy<Value = Outer> {
+        (
+            any::<bool>(),
+            any::<u32>(),
+            -1e12..1e12f64,
+            any::<Option<i32>>(),
+            inner(),
+            prop::collection::vec(text().prop_map(|text| Item { text }), 0..4),
+            prop::collection::vec(any::<u16>(), 0..4),
+        )
+            .prop_map(|(flag, unsigned, real, optional, inner, item, numbers)| Outer {
+                flag,
+                unsigned,
+                real,
+                optional,
+                inner,
+                item,
+                numbers,
+            })
+    }
+
+    #[test]
+    fn round_trips_arbitrary_structs() {
+        // seeded, so failures are reproducible
+        let rng = TestRng::from_seed(RngAlgorithm::ChaCha, &[47; 32]);
+        let mut runner = TestRunner::new_with_rng(Config::with_cases(512), rng);
+
+        runner
+            .run(&outer(), |value| {
+                let xml = String::from_utf8(super::to_vec(&value).unwrap()).unwrap();
+                let deserialized: Outer = super::from_str(&xml)
+                    .map_err(|err| TestCaseError::fail(format!("{} in xml: {}", err, xml)))?;
+                prop_assert_eq!(deserialized, value, "xml: {}", xml);
+                Ok(())
+            })
+            .unwrap();
+    }
+
+    #[test]
+    fn round_trips_empty_strings() {
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        #[serde(rename = "dto")]
+        struct Dto {
+            empty: String,
+            maybe_empty: Option<String>,
+        }
+
+        let test = Dto { empty: String::new(), maybe_empty: Some(String::new()) };
+
+        let xml = String::from_utf8(super::to_vec(&test).unwrap()).unwrap();
+        assert_eq!(xml, "<dto><empty></empty><maybe_empty></maybe_empty></dto>");
+
+        let deserialized: Dto = super::from_str(&xml).unwrap();
+        assert_eq!(deserialized, test);
+
+        // the client writes empty values as self-closing tags as well
+        let deserialized: Dto = super::from_str("<dto><empty/><maybe_empty/></dto>").unwrap();
+        assert_eq!(deserialized, test);
+    }
+}
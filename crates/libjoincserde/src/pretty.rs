@@ -0,0 +1,47 @@
+use crate::error::Result;
+use crate::Error;
+use quick_xml::events::Event;
+use quick_xml::{Reader, Writer};
+
+/// Re-indents the XML document, e.g. a reply of the client, for saving it readably.
+///
+/// Whitespace-only text between the tags is dropped, all other content is kept as is.
+pub fn pretty_print(xml: &str) -> Result<String> {
+    let mut reader = Reader::from_str(xml);
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+
+    let unexpected_xml = |err: quick_xml::Error| Error::UnexpectedXml(err.to_string());
+    loop {
+        match reader.read_event().map_err(unexpected_xml)? {
+            Event::Eof => break,
+            Event::Text(text) if text.iter().all(u8::is_ascii_whitespace) => (),
+            event => writer.write_event(event).map_err(unexpected_xml)?,
+        }
+    }
+
+    String::from_utf8(writer.into_inner()).map_err(|err| Error::UnexpectedXml(err.to_string()))
+}
+
+// ----- Tests -----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indents_nested_tags() {
+        let xml = "<client_state>\n<project>  <name>a &amp; b</name><flag/></project></client_state>";
+        assert_eq!(pretty_print(xml).unwrap(), "\
+<client_state>
+  <project>
+    <name>a &amp; b</name>
+    <flag/>
+  </project>
+</client_state>");
+    }
+
+    #[test]
+    fn fails_on_malformed_xml() {
+        assert!(pretty_print("<a><b></a>").is_err());
+    }
+}
@@ -1,11 +1,30 @@
 use crate::error::{Error, Result};
-use serde::{ser, Serialize};
+use serde::{ser, Deserialize, Serialize};
 use std::io;
 
+// name of the newtype struct telling the serializer to render a CDATA section
+const CDATA_NAME: &str = "$joinc::CData";
+
+/// A string which is wrapped in a CDATA section when serialized,
+/// e.g. for content which may contain markup like message bodies.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct CData(pub String);
+
+impl Serialize for CData {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_newtype_struct(CDATA_NAME, &self.0)
+    }
+}
+
 pub struct Serializer<W, F> {
     writer: W,
     formatter: F,
     tags: Vec<&'static str>,
+    cdata: bool,
 }
 
 impl<W, F> Serializer<W, F>
@@ -18,6 +37,7 @@ where
             writer,
             formatter,
             tags: Vec::with_capacity(8),
+            cdata: false,
         }
     }
 
@@ -53,6 +73,9 @@ where
     ser.finalize()
 }
 
+// the buffer size sufficing for most requests
+const DEFAULT_CAPACITY: usize = 256;
+
 pub fn to_vec<T>(value: &T) -> Result<Vec<u8>>
 where
     T: Serialize,
@@ -60,17 +83,27 @@ where
     to_vec_formatted(CompactFormatter, value)
 }
 
+/// Like [to_vec], but pre-allocates `hint` bytes, saving the reallocations when serializing large values.
+pub fn to_vec_with_capacity<T>(hint: usize, value: &T) -> Result<Vec<u8>>
+where
+    T: Serialize,
+{
+    let mut buffer = Vec::with_capacity(hint);
+    to_writer(&mut buffer, value)?;
+    Ok(buffer)
+}
+
 pub fn to_vec_formatted<F, T>(formatter: F, value: &T) -> Result<Vec<u8>>
 where
     F: Formatter,
     T: Serialize,
 {
-    let mut buffer = Vec::with_capacity(256);
+    let mut buffer = Vec::with_capacity(DEFAULT_CAPACITY);
     to_writer_formatted(&mut buffer, formatter, value)?;
     Ok(buffer)
 }
 
-impl<'a, W, F> ser::Serializer for &'a mut Serializer<W, F>
+impl<W, F> ser::Serializer for &mut Serializer<W, F>
 where
     W: io::Write,
     F: Formatter,
@@ -145,9 +178,15 @@ where
                 self.tags.last().ok_or(Error::IllegalState)?,
             )
             .map_err(Error::Io)?;
-        self.formatter
-            .render_field_value(&mut self.writer, v)
-            .map_err(Error::Io)?;
+        if self.cdata {
+            self.formatter
+                .render_cdata_value(&mut self.writer, v)
+                .map_err(Error::Io)?;
+        } else {
+            self.formatter
+                .render_field_value(&mut self.writer, v)
+                .map_err(Error::Io)?;
+        }
         self.formatter
             .render_closing_field_tag(
                 &mut self.writer,
@@ -186,11 +225,18 @@ where
             .map_err(Error::Io)
     }
 
-    fn serialize_newtype_struct<T>(self, _name: &'static str, _value: &T) -> Result<()>
+    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        unimplemented!()
+        if name != CDATA_NAME {
+            return value.serialize(self);
+        }
+
+        self.cdata = true;
+        let result = value.serialize(&mut *self);
+        self.cdata = false;
+        result
     }
 
     fn serialize_newtype_variant<T>(
@@ -259,7 +305,7 @@ where
     }
 }
 
-impl<'a, W, F> ser::SerializeSeq for &'a mut Serializer<W, F>
+impl<W, F> ser::SerializeSeq for &mut Serializer<W, F>
 where
     W: io::Write,
     F: Formatter,
@@ -279,7 +325,7 @@ where
     }
 }
 
-impl<'a, W, F> ser::SerializeTuple for &'a mut Serializer<W, F>
+impl<W, F> ser::SerializeTuple for &mut Serializer<W, F>
 where
     W: io::Write,
     F: Formatter,
@@ -299,7 +345,7 @@ where
     }
 }
 
-impl<'a, W, F> ser::SerializeTupleStruct for &'a mut Serializer<W, F>
+impl<W, F> ser::SerializeTupleStruct for &mut Serializer<W, F>
 where
     W: io::Write,
     F: Formatter,
@@ -319,7 +365,7 @@ where
     }
 }
 
-impl<'a, W, F> ser::SerializeTupleVariant for &'a mut Serializer<W, F>
+impl<W, F> ser::SerializeTupleVariant for &mut Serializer<W, F>
 where
     W: io::Write,
     F: Formatter,
@@ -340,7 +386,7 @@ where
 }
 
 // This doesn't really render a map, we're using when flattening struct variants into some dto
-impl<'a, W, F> ser::SerializeMap for &'a mut Serializer<W, F>
+impl<W, F> ser::SerializeMap for &mut Serializer<W, F>
 where
     W: io::Write,
     F: Formatter,
@@ -367,7 +413,7 @@ where
     }
 }
 
-impl<'a, W, F> ser::SerializeStruct for &'a mut Serializer<W, F>
+impl<W, F> ser::SerializeStruct for &mut Serializer<W, F>
 where
     W: io::Write,
     F: Formatter,
@@ -393,7 +439,7 @@ where
     }
 }
 
-impl<'a, W, F> ser::SerializeStructVariant for &'a mut Serializer<W, F>
+impl<W, F> ser::SerializeStructVariant for &mut Serializer<W, F>
 where
     W: io::Write,
     F: Formatter,
@@ -462,7 +508,39 @@ pub trait Formatter {
     where
         W: io::Write,
     {
-        writer.write_all(v)
+        let mut start = 0;
+        for (idx, byte) in v.iter().enumerate() {
+            let entity: &[u8] = match byte {
+                b'&' => b"&amp;",
+                b'<' => b"&lt;",
+                b'>' => b"&gt;",
+                _ => continue,
+            };
+            writer.write_all(&v[start..idx])?;
+            writer.write_all(entity)?;
+            start = idx + 1;
+        }
+        writer.write_all(&v[start..])
+    }
+
+    fn render_cdata_value<W>(&mut self, writer: &mut W, v: &[u8]) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        writer.write_all(b"<![CDATA[")?;
+        // a nested end marker has to be split across two sections
+        let mut sections = v.split(|&b| b == b'>').peekable();
+        while let Some(section) = sections.next() {
+            writer.write_all(section)?;
+            if sections.peek().is_some() {
+                if section.ends_with(b"]]") {
+                    writer.write_all(b"]]><![CDATA[>")?;
+                } else {
+                    writer.write_all(b">")?;
+                }
+            }
+        }
+        writer.write_all(b"]]>")
     }
 
     fn render_self_closing_tag<W>(&mut self, writer: &mut W, tag: &'static str) -> io::Result<()>
@@ -486,15 +564,25 @@ pub struct PrettyFormatter<'a> {
     indention_level: usize,
 }
 
+impl<'a> Default for PrettyFormatter<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> PrettyFormatter<'a> {
     pub fn new() -> Self {
         Self::with_indent(b"  ")
     }
 
     pub fn with_indent(indent: &'a [u8]) -> Self {
+        Self::with_style(indent, b"\n")
+    }
+
+    pub fn with_style(indent: &'a [u8], nl: &'a [u8]) -> Self {
         PrettyFormatter {
             default_formatter: CompactFormatter,
-            nl: b"\n",
+            nl,
             indent,
             indention_level: 0,
         }
@@ -525,7 +613,7 @@ impl<'a> Formatter for PrettyFormatter<'a> {
         self.inc_indent();
         self.default_formatter
             .render_opening_struct_tag(writer, tag)?;
-        writer.write_all(&self.nl)
+        writer.write_all(self.nl)
     }
 
     fn render_closing_struct_tag<W>(&mut self, writer: &mut W, tag: &'static str) -> io::Result<()>
@@ -536,7 +624,11 @@ impl<'a> Formatter for PrettyFormatter<'a> {
         self.render_indent(writer)?;
         self.default_formatter
             .render_closing_struct_tag(writer, tag)?;
-        writer.write_all(&self.nl)
+        // no newline after the root element, only between elements
+        if self.indention_level > 0 {
+            writer.write_all(self.nl)?;
+        }
+        Ok(())
     }
 
     fn render_opening_field_tag<W>(&mut self, writer: &mut W, tag: &'static str) -> io::Result<()>
@@ -553,7 +645,7 @@ impl<'a> Formatter for PrettyFormatter<'a> {
     {
         self.default_formatter
             .render_closing_field_tag(writer, tag)?;
-        writer.write_all(&self.nl)
+        writer.write_all(self.nl)
     }
 }
 
@@ -591,6 +683,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn serializes_with_capacity() {
+        #[derive(Serialize)]
+        #[serde(rename = "dto")]
+        struct Dto {
+            a: u32,
+        }
+
+        let serialized = super::to_vec_with_capacity(4096, &Dto { a: 47 }).unwrap();
+        assert_eq!(serialized, super::to_vec(&Dto { a: 47 }).unwrap());
+        assert!(serialized.capacity() >= 4096);
+    }
+
     #[test]
     fn serializes_empty_struct() {
         #[derive(Serialize)]
@@ -755,6 +860,108 @@ mod tests {
         );
     }
 
+    #[test]
+    fn serializes_options() {
+        #[derive(Serialize)]
+        #[serde(rename = "inner")]
+        struct InnerDTO {
+            b: Option<i32>,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "dto")]
+        struct Dto {
+            none: Option<String>,
+            some: Option<String>,
+            no_inner: Option<InnerDTO>,
+            inner: Option<InnerDTO>,
+        }
+
+        let test = Dto {
+            none: None,
+            some: Some("v".to_string()),
+            no_inner: None,
+            inner: Some(InnerDTO { b: None }),
+        };
+
+        let expected = "<dto><some>v</some><inner></inner></dto>";
+        assert_eq!(
+            String::from_utf8(super::to_vec(&test).unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn serializes_options_in_struct_variants() {
+        #[derive(Serialize)]
+        enum Op {
+            #[serde(rename(serialize = "do_foo"))]
+            Foo { a: Option<String>, b: Option<i32> }
+        }
+
+        #[derive(Serialize)]
+        struct Dto {
+            #[serde(flatten)]
+            dummy: Op,
+        }
+
+        let test = Op::Foo { a: None, b: Some(123) };
+        let expected = "<do_foo><b>123</b></do_foo>";
+
+        assert_eq!(
+            String::from_utf8(super::to_vec(&test).unwrap()).unwrap(),
+            expected
+        );
+
+        let test = Dto { dummy: Op::Foo { a: None, b: Some(123) } };
+        assert_eq!(
+            String::from_utf8(super::to_vec(&test).unwrap()).unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn serializes_cdata() {
+        use crate::ser::CData;
+
+        #[derive(Serialize)]
+        #[serde(rename = "dto")]
+        struct Dto {
+            body: CData,
+            tricky: CData,
+        }
+
+        let test = Dto {
+            body: CData("<b>hi</b>".to_string()),
+            tricky: CData("a]]>b".to_string()),
+        };
+
+        let expected = "<dto><body><![CDATA[<b>hi</b>]]></body><tricky><![CDATA[a]]]]><![CDATA[>b]]></tricky></dto>";
+        let serialized = String::from_utf8(super::to_vec(&test).unwrap()).unwrap();
+        assert_eq!(serialized, expected);
+    }
+
+    #[test]
+    fn escapes_field_values() {
+        use crate::ser::CData;
+
+        #[derive(Serialize)]
+        #[serde(rename = "dto")]
+        struct Dto {
+            content: String,
+            cdata: CData,
+        }
+
+        let test = Dto {
+            content: "a & <b>".to_string(),
+            cdata: CData("a & <b>".to_string()),
+        };
+
+        let expected = "<dto><content>a &amp; &lt;b&gt;</content><cdata><![CDATA[a & <b>]]></cdata></dto>";
+        let serialized = String::from_utf8(super::to_vec(&test).unwrap()).unwrap();
+        assert_eq!(serialized, expected);
+    }
+
     #[test]
     fn prints_pretty() {
         use crate::ser::PrettyFormatter;
@@ -777,15 +984,13 @@ mod tests {
             inner: InnerDTO { b: -11 },
         };
 
-        // yep, I know, it has a redundant newline
         let expected = "\
 <outer>
   <a>47</a>
   <inner>
     <b>-11</b>
   </inner>
-</outer>
-";
+</outer>";
         assert_eq!(
             String::from_utf8(
                 super::to_vec_formatted(PrettyFormatter::with_indent(b"  "), &test).unwrap()
@@ -794,4 +999,36 @@ mod tests {
             expected
         );
     }
+
+    #[test]
+    fn prints_pretty_with_style() {
+        use crate::ser::PrettyFormatter;
+
+        #[derive(Serialize)]
+        #[serde(rename = "inner")]
+        struct InnerDTO {
+            b: i32,
+        }
+
+        #[derive(Serialize)]
+        #[serde(rename = "outer")]
+        struct OuterDTO {
+            a: u32,
+            inner: InnerDTO,
+        }
+
+        let test = OuterDTO {
+            a: 47,
+            inner: InnerDTO { b: -11 },
+        };
+
+        let expected = "<outer>\r\n\t<a>47</a>\r\n\t<inner>\r\n\t\t<b>-11</b>\r\n\t</inner>\r\n</outer>";
+        assert_eq!(
+            String::from_utf8(
+                super::to_vec_formatted(PrettyFormatter::with_style(b"\t", b"\r\n"), &test).unwrap()
+            )
+            .unwrap(),
+            expected
+        );
+    }
 }